@@ -0,0 +1,39 @@
+use std::any::Any;
+
+use pumpkin::plugin::{Cancellable, Payload};
+
+use crate::Vote;
+
+/// Fired through Pumpkin's plugin manager for every vote VoteMe accepts.
+///
+/// Register an `EventHandler<VoteEvent>` at any `EventPriority`. A blocking
+/// handler can cancel the vote; handlers that run after it should check
+/// [`Cancellable::cancelled`] and leave the vote alone.
+#[derive(Debug, Clone)]
+pub struct VoteEvent {
+    pub vote: Vote,
+    cancelled: bool,
+}
+
+impl VoteEvent {
+    pub fn new(vote: Vote) -> Self {
+        VoteEvent { vote, cancelled: false }
+    }
+}
+
+impl Payload for VoteEvent {
+    fn get_name_static() -> &'static str { "VoteEvent" }
+    fn get_name(&self) -> &'static str { "VoteEvent" }
+    fn as_any(&self) -> &(dyn Any + 'static) { self }
+    fn as_any_mut(&mut self) -> &mut (dyn Any + 'static) { self }
+}
+
+impl Cancellable for VoteEvent {
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    fn set_cancelled(&mut self, cancelled: bool) {
+        self.cancelled = cancelled;
+    }
+}
@@ -1,21 +1,26 @@
 use std::sync::Mutex;
+#[cfg(feature = "pumpkin")]
 use pumpkin::plugin::Payload;
 
-#[derive(Clone)]
-pub struct Vote {
-    pub service_name: String,
-    pub username: String,
-    pub address: String,
-    pub timestamp: String,
-}
+#[cfg(feature = "pumpkin")]
+mod event;
+mod vote;
+
+#[cfg(feature = "pumpkin")]
+pub use event::VoteEvent;
+pub use vote::{Timestamp, Vote, VoteProtocol};
+
+type Listener = Box<dyn Fn(Vote) + Send + Sync>;
 
+/// Hands every accepted vote to the listeners registered with [`VoteService::on_vote`].
+#[derive(Default)]
 pub struct VoteService {
-    listeners: Mutex<Vec<Box<dyn Fn(Vote) + Send + Sync>>>,
+    listeners: Mutex<Vec<Listener>>,
 }
 
 impl VoteService {
     pub fn new() -> Self {
-        Self { listeners: Mutex::new(vec![]) }
+        Self::default()
     }
 
     pub fn on_vote<F>(&self, f: F)
@@ -32,6 +37,7 @@ impl VoteService {
     }
 }
 
+#[cfg(feature = "pumpkin")]
 impl Payload for VoteService {
     fn get_name_static() -> &'static str { "VoteService" }
     fn get_name(&self) -> &'static str { "VoteService" }
@@ -0,0 +1,197 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+
+/// Raw timestamps below this are seconds: 10^11 ms is March 1973, while 10^11 s
+/// is past the year 5000.
+const MILLIS_THRESHOLD: i64 = 100_000_000_000;
+
+/// A point in time, held as milliseconds since the Unix epoch.
+///
+/// Vote lists send either seconds or milliseconds; [`Timestamp::normalize`] and
+/// [`Timestamp::parse`] tell them apart, so every `Timestamp` is in milliseconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    pub fn from_millis(millis: i64) -> Self {
+        Timestamp(millis)
+    }
+
+    pub fn from_secs(secs: i64) -> Self {
+        Timestamp(secs.saturating_mul(1000))
+    }
+
+    pub fn now() -> Self {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| Timestamp(d.as_millis() as i64))
+            .unwrap_or_default()
+    }
+
+    /// A raw number from a vote list, in seconds or milliseconds.
+    pub fn normalize(raw: i64) -> Self {
+        if raw.abs() < MILLIS_THRESHOLD {
+            Self::from_secs(raw)
+        } else {
+            Self::from_millis(raw)
+        }
+    }
+
+    /// Reads a whole number of seconds or milliseconds, see [`Timestamp::normalize`].
+    pub fn parse(raw: &str) -> Option<Self> {
+        raw.trim().parse::<i64>().ok().map(Self::normalize)
+    }
+
+    pub fn as_millis(self) -> i64 {
+        self.0
+    }
+
+    pub fn as_secs(self) -> i64 {
+        self.0.div_euclid(1000)
+    }
+
+    pub fn to_system_time(self) -> SystemTime {
+        match u64::try_from(self.0) {
+            Ok(millis) => UNIX_EPOCH + Duration::from_millis(millis),
+            Err(_) => UNIX_EPOCH - Duration::from_millis(self.0.unsigned_abs()),
+        }
+    }
+}
+
+/// Milliseconds, as Votifier v1 blocks and v2 payloads carry them.
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Accepts a number or a numeric string, in seconds or milliseconds.
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(i64),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(raw) => Ok(Timestamp::normalize(raw)),
+            Raw::Text(raw) => Timestamp::parse(&raw).ok_or_else(|| D::Error::custom(format!("invalid timestamp {raw:?}"))),
+        }
+    }
+}
+
+/// How a vote reached VoteMe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VoteProtocol {
+    /// Votifier v1: an RSA-encrypted block.
+    V1,
+    /// NuVotifier v2: signed JSON.
+    V2,
+    /// The HTTP receiver or one of its site adapters.
+    Http,
+    /// Found by a poller in a vote list's API.
+    Poll,
+    /// `/voteme test` or a vote list's test button; nobody actually voted.
+    Test,
+}
+
+impl std::fmt::Display for VoteProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VoteProtocol::V1 => write!(f, "v1"),
+            VoteProtocol::V2 => write!(f, "v2"),
+            VoteProtocol::Http => write!(f, "http"),
+            VoteProtocol::Poll => write!(f, "poll"),
+            VoteProtocol::Test => write!(f, "test"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Vote {
+    pub service_name: String,
+    pub username: String,
+    pub address: String,
+    /// When the vote list says the vote was cast.
+    pub timestamp: Timestamp,
+    /// When VoteMe took the vote in.
+    pub received_at: Timestamp,
+    /// NuVotifier v2 `additionalData`; empty for everything else.
+    #[serde(default)]
+    pub additional_data: Map<String, Value>,
+    pub protocol: VoteProtocol,
+    /// Sent by `/voteme doctor` to check the listeners; not a real vote, so
+    /// rewards and vote records should skip it.
+    #[serde(default)]
+    pub probe: bool,
+}
+
+impl Vote {
+    /// A vote received now, without additional data.
+    pub fn new(
+        service_name: impl Into<String>,
+        username: impl Into<String>,
+        address: impl Into<String>,
+        timestamp: Timestamp,
+        protocol: VoteProtocol,
+    ) -> Self {
+        Vote {
+            service_name: service_name.into(),
+            username: username.into(),
+            address: address.into(),
+            timestamp,
+            received_at: Timestamp::now(),
+            additional_data: Map::new(),
+            protocol,
+            probe: false,
+        }
+    }
+
+    /// A vote from a list that sends no time of its own, so `timestamp` is the
+    /// arrival time.
+    pub fn received(
+        service_name: impl Into<String>,
+        username: impl Into<String>,
+        address: impl Into<String>,
+        protocol: VoteProtocol,
+    ) -> Self {
+        let now = Timestamp::now();
+        Vote {
+            received_at: now,
+            ..Vote::new(service_name, username, address, now, protocol)
+        }
+    }
+
+    /// Whether `timestamp` is only the arrival time, see [`Vote::received`].
+    pub fn stamped_on_arrival(&self) -> bool {
+        self.timestamp == self.received_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seconds_and_millis_are_told_apart() {
+        assert_eq!(Timestamp::normalize(1_700_000_000), Timestamp::from_millis(1_700_000_000_000));
+        assert_eq!(Timestamp::normalize(1_700_000_000_123), Timestamp::from_millis(1_700_000_000_123));
+        assert_eq!(Timestamp::parse(" 1700000000 ").map(Timestamp::as_secs), Some(1_700_000_000));
+        assert_eq!(Timestamp::parse("yesterday"), None);
+    }
+
+    #[test]
+    fn timestamps_deserialize_from_numbers_and_strings() {
+        let number: Timestamp = serde_json::from_str("1700000000").unwrap();
+        let text: Timestamp = serde_json::from_str("\"1700000000000\"").unwrap();
+        assert_eq!(number, text);
+        assert_eq!(serde_json::to_string(&number).unwrap(), "1700000000000");
+    }
+}
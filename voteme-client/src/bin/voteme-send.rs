@@ -0,0 +1,109 @@
+//! Sends one vote to a Votifier server and prints the whole exchange, for
+//! checking a server's setup without a real vote site.
+//!
+//! ```text
+//! voteme-send --host play.example.com --token <token> --service TestList --username Alex
+//! voteme-send --host play.example.com --key public.key --service TestList --username Alex
+//! ```
+
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::{ArgGroup, Parser};
+use voteme_client::{parse_public_key, send, Credentials, Timestamp, Vote, VoteProtocol};
+
+#[derive(Parser)]
+#[command(name = "voteme-send", version, about = "Send a test vote to a Votifier server")]
+#[command(group(ArgGroup::new("credentials").required(true).args(["key", "token"])))]
+struct Args {
+    #[arg(long)]
+    host: String,
+
+    #[arg(long, default_value_t = 8192)]
+    port: u16,
+
+    /// The server's public key file (PEM or base64), to send a v1 vote.
+    #[arg(long)]
+    key: Option<String>,
+
+    /// The server's token for the service, to send a v2 vote.
+    #[arg(long)]
+    token: Option<String>,
+
+    #[arg(long)]
+    service: String,
+
+    #[arg(long)]
+    username: String,
+
+    /// Address the vote claims to come from.
+    #[arg(long, default_value = "127.0.0.1")]
+    address: String,
+
+    /// Vote time in Unix seconds or milliseconds; defaults to now.
+    #[arg(long)]
+    timestamp: Option<i64>,
+
+    /// Seconds to wait for the whole exchange.
+    #[arg(long, default_value_t = 10)]
+    timeout: u64,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let credentials = match (&args.key, &args.token) {
+        (Some(path), _) => match std::fs::read_to_string(path)
+            .map_err(|e| format!("{path}: {e}"))
+            .and_then(|contents| parse_public_key(&contents).map_err(|e| format!("{path}: {e}")))
+        {
+            Ok(key) => Credentials::V1(key),
+            Err(e) => {
+                eprintln!("{e}");
+                return ExitCode::FAILURE;
+            }
+        },
+        (None, Some(token)) => Credentials::V2(token.clone()),
+        (None, None) => unreachable!("clap requires --key or --token"),
+    };
+    let protocol = match credentials {
+        Credentials::V1(_) => VoteProtocol::V1,
+        Credentials::V2(_) => VoteProtocol::V2,
+    };
+
+    let timestamp = args.timestamp.map(Timestamp::normalize).unwrap_or_else(Timestamp::now);
+    let vote = Vote::new(args.service, args.username, args.address, timestamp, protocol);
+    let address = format!("{}:{}", args.host, args.port);
+
+    println!("Sending {protocol} vote for {} from {} to {address}", vote.username, vote.service_name);
+    let exchange = match send(&address, &credentials, &vote, Duration::from_secs(args.timeout)).await {
+        Ok(exchange) => exchange,
+        Err(e) => {
+            println!("Failed: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    println!("< {}", exchange.greeting);
+    println!("> {} bytes", exchange.sent);
+    match &exchange.answer {
+        Some(answer) => println!("< {answer}"),
+        None => println!("  (v1 servers send no answer; check the server log)"),
+    }
+
+    match exchange.outcome() {
+        Ok(()) if exchange.answer.is_some() => {
+            println!("Accepted.");
+            ExitCode::SUCCESS
+        }
+        Ok(()) => {
+            println!("Sent.");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            println!("Failed: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,11 @@
+//! Votifier client: builds v1 (RSA-encrypted) and v2 (HMAC-signed) votes and
+//! sends them to any Votifier or NuVotifier endpoint, keeping what the server
+//! answered. It is VoteMe's own forwarding sender, so votes are built with the
+//! same `crypto::RSA` and `crypto::HMAC` the listeners check them with.
+
+pub use voteme_api::{Timestamp, Vote, VoteProtocol};
+pub use voteme_core::crypto::CryptoError;
+pub use voteme_core::forward::sender::{
+    check_answer, encode_v1, encode_v2, parse_public_key, send, send_over, Credentials, Exchange, SendError,
+};
+pub use voteme_core::net::vote_handler::V2_MAGIC;
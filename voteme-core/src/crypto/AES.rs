@@ -0,0 +1,34 @@
+use aes::Aes128;
+use cbc::{Decryptor, Encryptor};
+use cipher::{
+    block_padding::Pkcs7,
+    BlockDecryptMut, BlockEncryptMut, KeyIvInit,
+};
+
+use crate::crypto::CryptoError;
+
+const BLOCK_SIZE: usize = 16;
+
+pub fn decrypt(data: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let mut buf = data.to_vec();
+
+    let decrypted = Decryptor::<Aes128>::new_from_slices(key, iv)
+        .map_err(|e| CryptoError::Aes(e.to_string()))?
+        .decrypt_padded_mut::<Pkcs7>(&mut buf)
+        .map_err(|e| CryptoError::Aes(e.to_string()))?;
+
+    Ok(decrypted.to_vec())
+}
+
+pub fn encrypt(data: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    // Room for up to one extra block of padding.
+    let mut buf = vec![0u8; data.len() + BLOCK_SIZE];
+    buf[..data.len()].copy_from_slice(data);
+
+    let encrypted = Encryptor::<Aes128>::new_from_slices(key, iv)
+        .map_err(|e| CryptoError::Aes(e.to_string()))?
+        .encrypt_padded_mut::<Pkcs7>(&mut buf, data.len())
+        .map_err(|e| CryptoError::Aes(e.to_string()))?;
+
+    Ok(encrypted.to_vec())
+}
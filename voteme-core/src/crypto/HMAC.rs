@@ -0,0 +1,61 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Length of generated service tokens and connection challenges.
+const RANDOM_LEN: usize = 26;
+
+pub fn sign(data: &[u8], token: &str) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(token.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Checks a base64 HMAC-SHA256 signature in constant time.
+pub fn verify(data: &[u8], signature: &str, token: &str) -> bool {
+    let Ok(signature) = STANDARD.decode(signature.trim()) else {
+        return false;
+    };
+
+    let mut mac = HmacSha256::new_from_slice(token.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Checks a signature header value: base64 or hex, optionally prefixed with `sha256=`.
+pub fn verify_header(data: &[u8], header: &str, token: &str) -> bool {
+    let value = header.trim();
+    let value = value.strip_prefix("sha256=").unwrap_or(value);
+
+    let is_hex = value.len() == 64 && value.bytes().all(|b| b.is_ascii_hexdigit());
+    if !is_hex {
+        return verify(data, value, token);
+    }
+
+    let signature: Vec<u8> = (0..value.len())
+        .step_by(2)
+        .filter_map(|i| u8::from_str_radix(&value[i..i + 2], 16).ok())
+        .collect();
+    let mut mac = HmacSha256::new_from_slice(token.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Compares a presented shared secret with the token without leaking timing.
+pub fn secret_matches(presented: &str, token: &str) -> bool {
+    let mut mac = HmacSha256::new_from_slice(presented.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(b"voteme-secret");
+    mac.verify_slice(&sign(b"voteme-secret", token)).is_ok()
+}
+
+/// Random lowercase alphanumeric string, used for tokens and v2 challenges.
+pub fn random_string() -> String {
+    thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(RANDOM_LEN)
+        .map(|c| (c as char).to_ascii_lowercase())
+        .collect()
+}
@@ -0,0 +1,11 @@
+use rsa::{Pkcs1v15Encrypt, RsaPrivateKey, RsaPublicKey};
+
+use crate::crypto::CryptoError;
+
+pub fn encrypt(data: &[u8], pubkey: &RsaPublicKey) -> Result<Vec<u8>, CryptoError> {
+    Ok(pubkey.encrypt(&mut rand::thread_rng(), Pkcs1v15Encrypt, data)?)
+}
+
+pub fn decrypt(data: &[u8], privkey: &RsaPrivateKey) -> Result<Vec<u8>, CryptoError> {
+    Ok(privkey.decrypt(Pkcs1v15Encrypt, data)?)
+}
@@ -0,0 +1,253 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rsa::pkcs8::{
+    DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, EncryptedPrivateKeyInfo,
+};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::crypto::CryptoError;
+use crate::file;
+
+pub const RSA_DIR: &str = "plugins/VoteMe/rsa";
+
+/// On-disk key encoding.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyFormat {
+    /// PKCS#8 / X.509 PEM with `-----BEGIN ...-----` armour.
+    #[default]
+    Pem,
+    /// Bukkit Votifier / NuVotifier: base64 DER on a single line, no armour.
+    Votifier,
+}
+
+impl KeyFormat {
+    pub fn detect(contents: &str) -> KeyFormat {
+        if contents.contains("-----BEGIN") {
+            KeyFormat::Pem
+        } else {
+            KeyFormat::Votifier
+        }
+    }
+}
+
+/// Writes a key file readable only by the owner.
+fn write_key_file(full_path: &Path, contents: &[u8]) -> Result<(), CryptoError> {
+    file::write_private(full_path, contents).map_err(|e| CryptoError::Io(full_path.to_path_buf(), e))
+}
+
+/// Logs a warning if another user on the host can read the key file.
+pub fn warn_if_world_readable(full_path: &Path) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(meta) = fs::metadata(full_path)
+            && meta.permissions().mode() & 0o004 != 0
+        {
+            log::warn!(
+                "Key file {} is world-readable. Run `chmod 600` on it.",
+                full_path.display()
+            );
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = full_path;
+}
+
+/// Whether the private key file is stored as encrypted PKCS#8.
+pub fn is_encrypted(dir: &Path, filename: &str) -> Result<bool, CryptoError> {
+    let full_path = dir.join(filename);
+    let contents = fs::read_to_string(&full_path).map_err(|e| CryptoError::Io(full_path.clone(), e))?;
+    Ok(match KeyFormat::detect(&contents) {
+        KeyFormat::Pem => contents.contains("ENCRYPTED PRIVATE KEY"),
+        KeyFormat::Votifier => {
+            let der = decode_base64(&contents, &full_path)?;
+            RsaPrivateKey::from_pkcs8_der(&der).is_err()
+                && EncryptedPrivateKeyInfo::try_from(der.as_slice()).is_ok()
+        }
+    })
+}
+
+fn decode_base64(contents: &str, full_path: &Path) -> Result<Vec<u8>, CryptoError> {
+    let compact: String = contents.chars().filter(|c| !c.is_whitespace()).collect();
+    STANDARD
+        .decode(compact)
+        .map_err(|e| CryptoError::InvalidKey(full_path.to_path_buf(), format!("not base64 DER: {e}")))
+}
+
+/// Saves a private key to `dir`, encrypted as PKCS#8 when a passphrase is given.
+pub fn save_private(
+    dir: &Path,
+    key: &RsaPrivateKey,
+    filename: &str,
+    format: KeyFormat,
+    passphrase: Option<&str>,
+) -> Result<(), CryptoError> {
+    let full_path = dir.join(filename);
+    let encode_err = |e: rsa::pkcs8::Error| CryptoError::Encode(e.to_string());
+    let mut rng = rand::thread_rng();
+
+    let contents = match (format, passphrase) {
+        (KeyFormat::Pem, None) => key.to_pkcs8_pem(Default::default()).map_err(encode_err)?.to_string(),
+        (KeyFormat::Pem, Some(pass)) => key
+            .to_pkcs8_encrypted_pem(&mut rng, pass, Default::default())
+            .map_err(encode_err)?
+            .to_string(),
+        (KeyFormat::Votifier, None) => STANDARD.encode(key.to_pkcs8_der().map_err(encode_err)?.as_bytes()),
+        (KeyFormat::Votifier, Some(pass)) => STANDARD.encode(
+            key.to_pkcs8_encrypted_der(&mut rng, pass)
+                .map_err(encode_err)?
+                .as_bytes(),
+        ),
+    };
+    write_key_file(&full_path, contents.as_bytes())?;
+    log::info!("Saved private key to: {}", full_path.display());
+    Ok(())
+}
+
+/// Public key as one base64 DER line, the form vote sites ask for.
+pub fn public_key_line(key: &RsaPublicKey) -> Result<String, CryptoError> {
+    let der = key
+        .to_public_key_der()
+        .map_err(|e| CryptoError::Encode(e.to_string()))?;
+    Ok(STANDARD.encode(der.as_bytes()))
+}
+
+/// Reads a public key given as one base64 DER line, like [`public_key_line`] writes.
+pub fn parse_public_key_line(line: &str) -> Result<RsaPublicKey, CryptoError> {
+    let der = STANDARD
+        .decode(line.trim())
+        .map_err(|e| CryptoError::InvalidPublicKey(format!("not base64: {e}")))?;
+    RsaPublicKey::from_public_key_der(&der).map_err(|e| CryptoError::InvalidPublicKey(e.to_string()))
+}
+
+pub fn save_public(dir: &Path, key: &RsaPublicKey, filename: &str, format: KeyFormat) -> Result<(), CryptoError> {
+    let full_path = dir.join(filename);
+    let contents = match format {
+        KeyFormat::Pem => key
+            .to_public_key_pem(Default::default())
+            .map_err(|e| CryptoError::Encode(e.to_string()))?,
+        KeyFormat::Votifier => public_key_line(key)?,
+    };
+    write_key_file(&full_path, contents.as_bytes())?;
+    log::info!("Saved public key to: {}", full_path.display());
+    Ok(())
+}
+
+/// Loads a private key from `dir` in either PEM or Votifier format, plain or encrypted.
+pub fn load_private(dir: &Path, filename: &str, passphrase: Option<&str>) -> Result<RsaPrivateKey, CryptoError> {
+    let full_path = dir.join(filename);
+    let contents = fs::read_to_string(&full_path).map_err(|e| CryptoError::Io(full_path.clone(), e))?;
+    let invalid = |e: rsa::pkcs8::Error| CryptoError::InvalidKey(full_path.clone(), e.to_string());
+
+    let key = match KeyFormat::detect(&contents) {
+        KeyFormat::Pem if contents.contains("ENCRYPTED PRIVATE KEY") => {
+            let pass = passphrase.ok_or_else(|| CryptoError::Passphrase(full_path.clone()))?;
+            RsaPrivateKey::from_pkcs8_encrypted_pem(&contents, pass).map_err(invalid)?
+        }
+        KeyFormat::Pem => RsaPrivateKey::from_pkcs8_pem(&contents).map_err(invalid)?,
+        KeyFormat::Votifier => {
+            let der = decode_base64(&contents, &full_path)?;
+            match RsaPrivateKey::from_pkcs8_der(&der) {
+                Ok(key) => key,
+                Err(e) => match passphrase {
+                    Some(pass) => RsaPrivateKey::from_pkcs8_encrypted_der(&der, pass).map_err(invalid)?,
+                    None if EncryptedPrivateKeyInfo::try_from(der.as_slice()).is_ok() => {
+                        return Err(CryptoError::Passphrase(full_path));
+                    }
+                    None => return Err(invalid(e)),
+                },
+            }
+        }
+    };
+    warn_if_world_readable(&full_path);
+    log::info!("Loaded private key from: {}", full_path.display());
+    Ok(key)
+}
+
+/// Loads a public key from `dir` in either PEM or Votifier format.
+pub fn load_public(dir: &Path, filename: &str) -> Result<RsaPublicKey, CryptoError> {
+    let full_path = dir.join(filename);
+    let contents = fs::read_to_string(&full_path).map_err(|e| CryptoError::Io(full_path.clone(), e))?;
+    let key = match KeyFormat::detect(&contents) {
+        KeyFormat::Pem => RsaPublicKey::from_public_key_pem(&contents)
+            .map_err(|e| CryptoError::InvalidKey(full_path.clone(), e.to_string()))?,
+        KeyFormat::Votifier => RsaPublicKey::from_public_key_der(&decode_base64(&contents, &full_path)?)
+            .map_err(|e| CryptoError::InvalidKey(full_path.clone(), e.to_string()))?,
+    };
+    log::info!("Loaded public key from: {}", full_path.display());
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::RSAKeyGen;
+
+    fn round_trip(format: KeyFormat, passphrase: Option<&str>) {
+        let name = format!("{format:?}-{}", passphrase.is_some());
+        let dir = std::env::temp_dir().join(format!("voteme-rsaio-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let (private, public) = RSAKeyGen::generate(1024).unwrap();
+
+        save_private(&dir, &private, "private.key", format, passphrase).unwrap();
+        save_public(&dir, &public, "public.key", format).unwrap();
+
+        for file in ["private.key", "public.key"] {
+            let contents = fs::read_to_string(dir.join(file)).unwrap();
+            assert_eq!(KeyFormat::detect(&contents), format);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = fs::metadata(dir.join(file)).unwrap().permissions().mode();
+                assert_eq!(mode & 0o777, 0o600, "{file}");
+            }
+        }
+        assert_eq!(is_encrypted(&dir, "private.key").unwrap(), passphrase.is_some());
+        assert_eq!(load_private(&dir, "private.key", passphrase).unwrap(), private);
+        assert_eq!(load_public(&dir, "public.key").unwrap(), public);
+        if passphrase.is_some() {
+            assert!(matches!(
+                load_private(&dir, "private.key", None),
+                Err(CryptoError::Passphrase(_))
+            ));
+            assert!(load_private(&dir, "private.key", Some("wrong")).is_err());
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn pem_keys_round_trip() {
+        round_trip(KeyFormat::Pem, None);
+    }
+
+    #[test]
+    fn votifier_keys_round_trip() {
+        round_trip(KeyFormat::Votifier, None);
+    }
+
+    #[test]
+    fn encrypted_pem_keys_round_trip() {
+        round_trip(KeyFormat::Pem, Some("correct horse"));
+    }
+
+    #[test]
+    fn encrypted_votifier_keys_round_trip() {
+        round_trip(KeyFormat::Votifier, Some("correct horse"));
+    }
+
+    #[test]
+    fn votifier_public_key_line_is_read_back() {
+        let dir = std::env::temp_dir().join(format!("voteme-rsaio-line-{}", std::process::id()));
+        let (_, public) = RSAKeyGen::generate(1024).unwrap();
+        let line = public_key_line(&public).unwrap();
+        assert_eq!(KeyFormat::detect(&line), KeyFormat::Votifier);
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("public.key"), format!("{line}\n")).unwrap();
+        assert_eq!(load_public(&dir, "public.key").unwrap(), public);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
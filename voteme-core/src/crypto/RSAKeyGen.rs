@@ -0,0 +1,14 @@
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use crate::crypto::CryptoError;
+use rand::thread_rng;
+
+pub fn generate(bits: usize) -> Result<(RsaPrivateKey, RsaPublicKey), CryptoError> {
+    log::warn!("============================");
+    log::warn!("Generating {}-bit RSA keypair...", bits);
+    log::warn!("============================");
+
+    let mut rng = thread_rng();
+    let privkey = RsaPrivateKey::new(&mut rng, bits).map_err(CryptoError::KeyGen)?;
+    let pubkey = RsaPublicKey::from(&privkey);
+    Ok((privkey, pubkey))
+}
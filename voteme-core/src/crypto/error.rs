@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum CryptoError {
+    /// Reading or writing a key file failed.
+    Io(PathBuf, std::io::Error),
+    /// A key file exists but could not be decoded.
+    InvalidKey(PathBuf, String),
+    /// The key file is encrypted and no passphrase is configured.
+    Passphrase(PathBuf),
+    /// A key could not be encoded for storage.
+    Encode(String),
+    /// A public key given inline, not in a file, could not be decoded.
+    InvalidPublicKey(String),
+    KeyGen(rsa::Error),
+    Rsa(rsa::Error),
+    Aes(String),
+}
+
+impl std::fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CryptoError::Io(path, e) => write!(f, "key file {}: {e}", path.display()),
+            CryptoError::InvalidKey(path, msg) => write!(f, "invalid key in {}: {msg}", path.display()),
+            CryptoError::Passphrase(path) => {
+                write!(f, "{} is encrypted but no passphrase is configured", path.display())
+            }
+            CryptoError::Encode(msg) => write!(f, "key encoding failed: {msg}"),
+            CryptoError::InvalidPublicKey(msg) => write!(f, "invalid public key: {msg}"),
+            CryptoError::KeyGen(e) => write!(f, "key generation failed: {e}"),
+            CryptoError::Rsa(e) => write!(f, "rsa: {e}"),
+            CryptoError::Aes(msg) => write!(f, "aes: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+impl From<rsa::Error> for CryptoError {
+    fn from(value: rsa::Error) -> Self {
+        CryptoError::Rsa(value)
+    }
+}
@@ -0,0 +1,395 @@
+use rsa::pkcs8::EncodePublicKey;
+use rsa::traits::PublicKeyParts;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::crypto::RSAIO::{KeyFormat, RSA_DIR};
+use crate::crypto::{CryptoError, RSAIO, RSAKeyGen};
+
+pub const PRIVATE_KEY_FILE: &str = "private.key";
+pub const PUBLIC_KEY_FILE: &str = "public.key";
+/// Retired keys live in `rsa/retired/private-<unix seconds>.key`, with a `-<n>`
+/// suffix after the seconds when several rotations happen within one second.
+pub const RETIRED_DIR: &str = "retired";
+
+pub struct ManagedKey {
+    pub private: RsaPrivateKey,
+    pub public: RsaPublicKey,
+    /// Unix seconds the key was rotated out, `None` for the current key.
+    pub retired_at: Option<u64>,
+    /// Hex SHA-256 of the X.509 DER public key.
+    pub fingerprint: String,
+}
+
+impl ManagedKey {
+    pub fn new(private: RsaPrivateKey, retired_at: Option<u64>) -> Self {
+        let public = RsaPublicKey::from(&private);
+        let fingerprint = fingerprint(&public);
+        ManagedKey {
+            private,
+            public,
+            retired_at,
+            fingerprint,
+        }
+    }
+
+    pub fn bits(&self) -> usize {
+        self.public.size() * 8
+    }
+
+    /// Short name used in logs, e.g. `current 1a2b3c4d`.
+    pub fn label(&self) -> String {
+        let short = &self.fingerprint[..8];
+        match self.retired_at {
+            None => format!("current {short}"),
+            Some(at) => format!("retired@{at} {short}"),
+        }
+    }
+}
+
+pub fn fingerprint(key: &RsaPublicKey) -> String {
+    let der = key
+        .to_public_key_der()
+        .map(|der| der.as_bytes().to_vec())
+        .unwrap_or_default();
+    Sha256::digest(der).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+struct KeySet {
+    current: Arc<ManagedKey>,
+    retired: Vec<Arc<ManagedKey>>,
+}
+
+/// Owns the `rsa/` directory: the current keypair plus retired keys that are
+/// still accepted for a grace period after a rotation.
+pub struct KeyManager {
+    dir: PathBuf,
+    format: KeyFormat,
+    grace: Duration,
+    passphrase: Option<String>,
+    keys: RwLock<KeySet>,
+}
+
+impl KeyManager {
+    /// Loads the keys in `dir`, generating a keypair on first start. A key whose
+    /// size no longer matches `bits` is rotated.
+    ///
+    /// With a passphrase, private keys are stored as encrypted PKCS#8 and an
+    /// existing plain key is re-saved encrypted.
+    pub fn init(
+        dir: &Path,
+        bits: usize,
+        format: KeyFormat,
+        grace: Duration,
+        passphrase: Option<String>,
+    ) -> Result<Self, CryptoError> {
+        let private_path = dir.join(PRIVATE_KEY_FILE);
+        let pass = passphrase.as_deref();
+
+        let current = if private_path.exists() {
+            let privkey = RSAIO::load_private(dir, PRIVATE_KEY_FILE, pass)?;
+            if pass.is_some() && !RSAIO::is_encrypted(dir, PRIVATE_KEY_FILE)? {
+                log::info!("Encrypting existing private key with the configured passphrase");
+                RSAIO::save_private(dir, &privkey, PRIVATE_KEY_FILE, format, pass)?;
+            }
+            privkey
+        } else {
+            let (privkey, pubkey) = RSAKeyGen::generate(bits)?;
+            RSAIO::save_private(dir, &privkey, PRIVATE_KEY_FILE, format, pass)?;
+            RSAIO::save_public(dir, &pubkey, PUBLIC_KEY_FILE, format)?;
+            privkey
+        };
+
+        let manager = KeyManager {
+            dir: dir.to_path_buf(),
+            format,
+            grace,
+            passphrase,
+            keys: RwLock::new(KeySet {
+                current: Arc::new(ManagedKey::new(current, None)),
+                retired: Vec::new(),
+            }),
+        };
+        manager.load_retired()?;
+
+        let current = manager.current();
+        if current.bits() != bits {
+            log::warn!(
+                "rsa_bits changed from {} to {}, rotating key. Update the public key on your vote sites within {}h.",
+                current.bits(),
+                bits,
+                grace.as_secs() / 3600
+            );
+            manager.rotate(bits)?;
+        }
+
+        Ok(manager)
+    }
+
+    /// In-memory manager around a single key, nothing is read from or written to disk.
+    pub fn from_key(private: RsaPrivateKey, format: KeyFormat, grace: Duration) -> Self {
+        KeyManager {
+            dir: PathBuf::from(RSA_DIR),
+            format,
+            grace,
+            passphrase: None,
+            keys: RwLock::new(KeySet {
+                current: Arc::new(ManagedKey::new(private, None)),
+                retired: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn current(&self) -> Arc<ManagedKey> {
+        self.keys.read().unwrap().current.clone()
+    }
+
+    /// Current key first, then retired keys still inside the grace period.
+    pub fn decryption_keys(&self) -> Vec<Arc<ManagedKey>> {
+        let keys = self.keys.read().unwrap();
+        let now = now_secs();
+
+        let mut out = vec![keys.current.clone()];
+        out.extend(
+            keys.retired
+                .iter()
+                .filter(|k| k.retired_at.is_some_and(|at| at + self.grace.as_secs() > now))
+                .cloned(),
+        );
+        out
+    }
+
+    /// Re-reads the current and retired keys from disk.
+    pub fn reload(&self) -> Result<(), CryptoError> {
+        let private = RSAIO::load_private(&self.dir, PRIVATE_KEY_FILE, self.passphrase.as_deref())?;
+        self.keys.write().unwrap().current = Arc::new(ManagedKey::new(private, None));
+        self.load_retired()
+    }
+
+    /// Reads both current key files and checks they hold the key in use.
+    pub fn check_files(&self) -> Result<(), CryptoError> {
+        let current = self.current();
+        let private = RSAIO::load_private(&self.dir, PRIVATE_KEY_FILE, self.passphrase.as_deref())?;
+        if RsaPublicKey::from(&private) != current.public {
+            return Err(CryptoError::InvalidKey(
+                self.dir.join(PRIVATE_KEY_FILE),
+                "not the key in use, run /voteme reload".to_string(),
+            ));
+        }
+        if RSAIO::load_public(&self.dir, PUBLIC_KEY_FILE)? != current.public {
+            return Err(CryptoError::InvalidKey(
+                self.dir.join(PUBLIC_KEY_FILE),
+                "does not match the private key".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Generates a new keypair and retires the current one for the grace period.
+    pub fn rotate(&self, bits: usize) -> Result<Arc<ManagedKey>, CryptoError> {
+        let (privkey, pubkey) = RSAKeyGen::generate(bits)?;
+        let retired_at = now_secs();
+
+        let old = self.current();
+        let pass = self.passphrase.as_deref();
+        let dir = self.dir.as_path();
+        let id = self.free_retired_id(retired_at);
+        RSAIO::save_private(dir, &old.private, &retired_file("private", &id), self.format, pass)?;
+        RSAIO::save_public(dir, &old.public, &retired_file("public", &id), self.format)?;
+        RSAIO::save_private(dir, &privkey, PRIVATE_KEY_FILE, self.format, pass)?;
+        RSAIO::save_public(dir, &pubkey, PUBLIC_KEY_FILE, self.format)?;
+
+        let new = Arc::new(ManagedKey::new(privkey, None));
+        {
+            let mut keys = self.keys.write().unwrap();
+            let old = Arc::new(ManagedKey::new(old.private.clone(), Some(retired_at)));
+            log::info!("Retired key {} for {}h", old.label(), self.grace.as_secs() / 3600);
+            keys.retired.push(old);
+            keys.current = new.clone();
+        }
+        log::info!("Rotated to new key {}", new.label());
+
+        Ok(new)
+    }
+
+    /// File id for a key retired at `retired_at` that doesn't overwrite an earlier one.
+    fn free_retired_id(&self, retired_at: u64) -> String {
+        let dir = self.dir.join(RETIRED_DIR);
+        let mut id = retired_at.to_string();
+        let mut n = 1;
+        while dir.join(format!("private-{id}.key")).exists() {
+            id = format!("{retired_at}-{n}");
+            n += 1;
+        }
+        id
+    }
+
+    /// Loads retired keys still inside the grace period and deletes expired ones.
+    fn load_retired(&self) -> Result<(), CryptoError> {
+        let dir = self.dir.join(RETIRED_DIR);
+        if !dir.exists() {
+            return Ok(());
+        }
+
+        let entries = fs::read_dir(&dir).map_err(|e| CryptoError::Io(dir.clone(), e))?;
+        let now = now_secs();
+        let mut retired = Vec::new();
+
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Some(id) = name.strip_prefix("private-").and_then(|rest| rest.strip_suffix(".key")) else {
+                continue;
+            };
+            let Some(retired_at) = id.split('-').next().and_then(|ts| ts.parse::<u64>().ok()) else {
+                continue;
+            };
+
+            if retired_at + self.grace.as_secs() <= now {
+                log::info!("Deleting expired retired key {}", name);
+                let _ = fs::remove_file(entry.path());
+                let _ = fs::remove_file(dir.join(format!("public-{id}.key")));
+                continue;
+            }
+
+            let private = RSAIO::load_private(&self.dir, &retired_file("private", id), self.passphrase.as_deref())?;
+            retired.push(Arc::new(ManagedKey::new(private, Some(retired_at))));
+        }
+
+        self.keys.write().unwrap().retired = retired;
+        Ok(())
+    }
+}
+
+/// Reads the key passphrase from the environment variable `env`, or else from `file`.
+/// Warns when the file sits inside the plugin folder next to the keys it protects.
+pub fn load_passphrase(env: &str, file: Option<&str>) -> Result<Option<String>, CryptoError> {
+    if !env.is_empty()
+        && let Ok(pass) = std::env::var(env)
+        && !pass.is_empty()
+    {
+        return Ok(Some(pass));
+    }
+
+    let Some(file) = file.filter(|f| !f.is_empty()) else {
+        return Ok(None);
+    };
+
+    let path = Path::new(file);
+    let plugin_dir = Path::new(RSA_DIR).parent().unwrap_or(Path::new(RSA_DIR));
+    if let (Ok(path), Ok(plugin_dir)) = (path.canonicalize(), plugin_dir.canonicalize())
+        && path.starts_with(plugin_dir)
+    {
+        log::warn!("Key passphrase file {} is inside the plugin folder; move it elsewhere.", file);
+    }
+
+    let pass = fs::read_to_string(path).map_err(|e| CryptoError::Io(path.to_path_buf(), e))?;
+    Ok(Some(pass.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+fn retired_file(kind: &str, id: &str) -> String {
+    format!("{RETIRED_DIR}/{kind}-{id}.key")
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::RSA;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("voteme-keys-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn retired_files(dir: &Path) -> usize {
+        fs::read_dir(dir.join(RETIRED_DIR)).map(|entries| entries.count()).unwrap_or(0)
+    }
+
+    #[test]
+    fn retired_keys_decrypt_during_the_grace_period() {
+        let dir = temp_dir("grace");
+        let manager = KeyManager::init(&dir, 1024, KeyFormat::Pem, HOUR, None).unwrap();
+        let original = manager.current();
+        let block = RSA::encrypt(b"VOTE", &original.public).unwrap();
+
+        // Two rotations inside one second must not overwrite each other.
+        manager.rotate(1024).unwrap();
+        manager.rotate(1024).unwrap();
+        assert_eq!(retired_files(&dir), 4);
+        assert_ne!(manager.current().fingerprint, original.fingerprint);
+
+        let reloaded = KeyManager::init(&dir, 1024, KeyFormat::Pem, HOUR, None).unwrap();
+        let keys = reloaded.decryption_keys();
+        assert_eq!(keys.len(), 3);
+        let decrypting = keys
+            .iter()
+            .find(|key| RSA::decrypt(&block, &key.private).is_ok())
+            .unwrap();
+        assert_eq!(decrypting.fingerprint, original.fingerprint);
+        assert!(decrypting.retired_at.is_some());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn plain_key_is_encrypted_once_a_passphrase_is_set() {
+        let dir = temp_dir("encrypt");
+        let plain = KeyManager::init(&dir, 1024, KeyFormat::Votifier, HOUR, None).unwrap();
+        assert!(!RSAIO::is_encrypted(&dir, PRIVATE_KEY_FILE).unwrap());
+
+        let encrypted = KeyManager::init(&dir, 1024, KeyFormat::Votifier, HOUR, Some("secret".to_string())).unwrap();
+        assert!(RSAIO::is_encrypted(&dir, PRIVATE_KEY_FILE).unwrap());
+        assert_eq!(encrypted.current().fingerprint, plain.current().fingerprint);
+
+        assert!(matches!(
+            KeyManager::init(&dir, 1024, KeyFormat::Votifier, HOUR, None),
+            Err(CryptoError::Passphrase(_))
+        ));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn passphrase_comes_from_the_environment_before_the_file() {
+        let dir = temp_dir("passphrase");
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("passphrase.txt");
+        fs::write(&file, "from-file\r\n").unwrap();
+        let file = file.to_str();
+
+        let env = format!("VOTEME_TEST_PASSPHRASE_{}", std::process::id());
+        assert_eq!(load_passphrase(&env, file).unwrap().as_deref(), Some("from-file"));
+        assert_eq!(load_passphrase("", None).unwrap(), None);
+
+        // SAFETY: the variable name is unique to this test, nothing else reads it.
+        unsafe { std::env::set_var(&env, "from-env") };
+        assert_eq!(load_passphrase(&env, file).unwrap().as_deref(), Some("from-env"));
+        unsafe { std::env::remove_var(&env) };
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn retired_keys_are_deleted_after_the_grace_period() {
+        let dir = temp_dir("expiry");
+        let manager = KeyManager::init(&dir, 1024, KeyFormat::Pem, Duration::ZERO, None).unwrap();
+        manager.rotate(1024).unwrap();
+        assert_eq!(retired_files(&dir), 2);
+        assert_eq!(manager.decryption_keys().len(), 1);
+
+        let reloaded = KeyManager::init(&dir, 1024, KeyFormat::Pem, Duration::ZERO, None).unwrap();
+        assert_eq!(reloaded.decryption_keys().len(), 1);
+        assert_eq!(retired_files(&dir), 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
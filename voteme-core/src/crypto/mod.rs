@@ -0,0 +1,10 @@
+pub mod error;
+pub mod key_manager;
+pub mod RSA;
+pub mod RSAKeyGen;
+pub mod RSAIO;
+pub mod AES;
+pub mod HMAC;
+
+pub use error::CryptoError;
+pub use key_manager::KeyManager;
@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+use voteme_api::{Timestamp, Vote, VoteProtocol, VoteService};
+
+use crate::file::config::ListenerConfig;
+use crate::file::tokens::DEFAULT_SERVICE;
+use crate::file::{Config, ConfigManager};
+use crate::forward::sender::{self, Credentials};
+use crate::net::vote_handler::Protocol;
+use crate::state::{ListenerStatus, VoteMeState};
+
+/// Address and username carried by doctor votes, so they're recognisable in logs.
+/// Listeners mark them with [`Vote::probe`] and the forwarder never passes them on.
+pub const DOCTOR_ADDRESS: &str = "voteme-doctor";
+pub const DOCTOR_USERNAME: &str = "VoteMeDoctor";
+
+/// How long one probe vote may take to be answered and emitted.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Doctor votes on their way through the listeners, by timestamp. Listeners
+/// ask it which votes to mark as probes, and it reports the marked ones to
+/// whoever is waiting for them once they're emitted.
+#[derive(Default)]
+pub struct VoteProbe {
+    waiting: Mutex<HashMap<Timestamp, oneshot::Sender<()>>>,
+}
+
+impl VoteProbe {
+    pub fn attach(service: &VoteService) -> Arc<Self> {
+        let probe = Arc::new(VoteProbe::default());
+        let listener = probe.clone();
+        service.on_vote(move |vote| listener.observe(&vote));
+        probe
+    }
+
+    fn expect(&self, timestamp: Timestamp) -> oneshot::Receiver<()> {
+        let (tx, rx) = oneshot::channel();
+        self.waiting.lock().unwrap().insert(timestamp, tx);
+        rx
+    }
+
+    fn forget(&self, timestamp: Timestamp) {
+        self.waiting.lock().unwrap().remove(&timestamp);
+    }
+
+    /// Whether `vote` is a doctor vote still being waited for.
+    pub fn expects(&self, vote: &Vote) -> bool {
+        vote.username == DOCTOR_USERNAME && self.waiting.lock().unwrap().contains_key(&vote.timestamp)
+    }
+
+    fn observe(&self, vote: &Vote) {
+        if !is_doctor_vote(vote) {
+            return;
+        }
+        if let Some(tx) = self.waiting.lock().unwrap().remove(&vote.timestamp) {
+            let _ = tx.send(());
+        }
+    }
+}
+
+pub fn is_doctor_vote(vote: &Vote) -> bool {
+    vote.probe
+}
+
+pub enum Outcome {
+    Pass(String),
+    Fail(String),
+    /// Not run because an earlier step it depends on failed.
+    Skipped,
+}
+
+pub struct Check {
+    pub step: &'static str,
+    pub outcome: Outcome,
+}
+
+impl std::fmt::Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.outcome {
+            Outcome::Pass(detail) => write!(f, "[PASS] {}: {detail}", self.step),
+            Outcome::Fail(detail) => write!(f, "[FAIL] {}: {detail}", self.step),
+            Outcome::Skipped => write!(f, "[SKIP] {}", self.step),
+        }
+    }
+}
+
+/// Results of one `/voteme doctor` run, in the order the steps ran.
+#[derive(Default)]
+pub struct Report {
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    fn record(&mut self, step: &'static str, result: Result<String, String>) -> bool {
+        let passed = result.is_ok();
+        let outcome = match result {
+            Ok(detail) => Outcome::Pass(detail),
+            Err(detail) => Outcome::Fail(detail),
+        };
+        self.checks.push(Check { step, outcome });
+        passed
+    }
+
+    fn skip(&mut self, step: &'static str) {
+        self.checks.push(Check { step, outcome: Outcome::Skipped });
+    }
+
+    /// The first step that failed, if any.
+    pub fn failed_step(&self) -> Option<&'static str> {
+        self.checks
+            .iter()
+            .find(|c| matches!(c.outcome, Outcome::Fail(_)))
+            .map(|c| c.step)
+    }
+}
+
+/// Self-test behind `/voteme doctor`: checks the files on disk, then sends a v1
+/// and a v2 vote for `service` to a running listener over loopback and waits for
+/// each to reach the VoteService listeners.
+pub struct Doctor;
+
+impl Doctor {
+    pub async fn run(state: &VoteMeState, service: Option<&str>) -> Report {
+        let mut report = Report::default();
+        report.record("Config", Self::check_config().await);
+        report.record("Keys", Self::check_keys(state));
+        let service = match service {
+            Some(service) => service.to_string(),
+            None => default_service(state),
+        };
+        let tokens = Self::check_tokens(state, &service);
+        let token = tokens.as_ref().ok().map(|(_, token)| token.clone());
+        report.record("Tokens", tokens.map(|(detail, _)| detail));
+        Self::probe(state, &service, token, &mut report).await;
+        report
+    }
+
+    async fn check_config() -> Result<String, String> {
+        let mut config = Config::default();
+        ConfigManager::new_default()
+            .load_config(&mut config)
+            .await
+            .map_err(|e| format!("Config.toml does not load: {e}"))?;
+        Ok(format!("Config.toml parses, {} listener(s)", config.listeners().len()))
+    }
+
+    fn check_keys(state: &VoteMeState) -> Result<String, String> {
+        state.keys.check_files().map_err(|e| e.to_string())?;
+        let key = state.keys.current();
+        Ok(format!("{}-bit key {} matches the key files", key.bits(), &key.fingerprint[..8]))
+    }
+
+    fn check_tokens(state: &VoteMeState, service: &str) -> Result<(String, String), String> {
+        let tokens = state.tokens.read_file()?;
+        let token = state
+            .tokens
+            .get(service)
+            .ok_or_else(|| format!("No token for {service}, add one with /voteme token add {service}"))?;
+        Ok((format!("{} service(s), token for {service} loaded", tokens.tokens.len()), token))
+    }
+
+    /// Sends the v1 and v2 probe votes, recording one step for each.
+    async fn probe(state: &VoteMeState, service: &str, token: Option<String>, report: &mut Report) {
+        let config = state.config();
+        let v1 = Self::find_listener(state, &config, service, Protocol::V1);
+        let v2 = Self::find_listener(state, &config, service, Protocol::V2);
+        if !report.record("Listener", v1.clone().or(v2.clone()).map(|a| format!("{a} is listening"))) {
+            report.skip("v1 vote");
+            report.skip("v2 vote");
+            return;
+        }
+
+        // Distinct timestamps keep the two votes apart for the probe and the dedupe cache.
+        let timestamp = Timestamp::now();
+        let credentials = Credentials::V1(state.keys.current().public.clone());
+        let result = match v1 {
+            Ok(address) => Self::send(state, &address, &credentials, service, timestamp).await,
+            Err(e) => Err(e),
+        };
+        report.record("v1 vote", result);
+
+        match (v2, token) {
+            (Ok(address), Some(token)) => {
+                let credentials = Credentials::V2(token);
+                let timestamp = Timestamp::from_millis(timestamp.as_millis() + 1);
+                let result = Self::send(state, &address, &credentials, service, timestamp).await;
+                report.record("v2 vote", result);
+            }
+            (Err(e), _) => {
+                report.record("v2 vote", Err(e));
+            }
+            (Ok(_), None) => report.skip("v2 vote"),
+        }
+    }
+
+    /// Loopback address of a running network listener taking `protocol` votes for `service`.
+    fn find_listener(
+        state: &VoteMeState,
+        config: &Config,
+        service: &str,
+        protocol: Protocol,
+    ) -> Result<String, String> {
+        let statuses = state.listeners.lock().unwrap().clone();
+        let candidates: Vec<ListenerConfig> = config
+            .listeners()
+            .into_iter()
+            .filter(|l| !l.is_unix() && l.scope.allows_protocol(protocol) && l.scope.allows_service(service))
+            .collect();
+        if candidates.is_empty() {
+            return Err(format!("No network listener takes {protocol} votes for {service}"));
+        }
+
+        candidates
+            .iter()
+            .find(|l| matches!(statuses.get(&l.address), Some(ListenerStatus::Listening)))
+            .map(|l| loopback(&l.address))
+            .ok_or_else(|| {
+                let address = &candidates[0].address;
+                match statuses.get(address) {
+                    Some(status) => format!("{address} is {status}"),
+                    None => format!("{address} has not started"),
+                }
+            })
+    }
+
+    async fn send(
+        state: &VoteMeState,
+        address: &str,
+        credentials: &Credentials,
+        service: &str,
+        timestamp: Timestamp,
+    ) -> Result<String, String> {
+        let protocol = match credentials {
+            Credentials::V1(_) => VoteProtocol::V1,
+            Credentials::V2(_) => VoteProtocol::V2,
+        };
+        let vote = Vote::new(service, DOCTOR_USERNAME, DOCTOR_ADDRESS, timestamp, protocol);
+
+        let arrived = state.probe.expect(timestamp);
+        let sent = sender::send(address, credentials, &vote, PROBE_TIMEOUT)
+            .await
+            .and_then(|exchange| exchange.outcome());
+        if let Err(e) = sent {
+            state.probe.forget(timestamp);
+            return Err(format!("{address}: {e}"));
+        }
+
+        match tokio::time::timeout(PROBE_TIMEOUT, arrived).await {
+            Ok(Ok(())) => Ok(format!("sent to {address} and received by VoteService listeners")),
+            _ => {
+                state.probe.forget(timestamp);
+                Err(format!("sent to {address} but never emitted, check the server log"))
+            }
+        }
+    }
+}
+
+/// `default` when it has a token, else the first service that does.
+fn default_service(state: &VoteMeState) -> String {
+    let services = state.tokens.services();
+    if services.iter().any(|s| s == DEFAULT_SERVICE) {
+        return DEFAULT_SERVICE.to_string();
+    }
+    services.into_iter().next().unwrap_or_else(|| DEFAULT_SERVICE.to_string())
+}
+
+/// Where to connect to reach a listener bound to `address`: wildcard binds
+/// are reached through the loopback address of the same family.
+fn loopback(address: &str) -> String {
+    match address.parse::<SocketAddr>() {
+        Ok(mut addr) if addr.ip().is_unspecified() => {
+            let ip = match addr.ip() {
+                IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+                IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::LOCALHOST),
+            };
+            addr.set_ip(ip);
+            addr.to_string()
+        }
+        _ => address.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyManager, RSAIO::KeyFormat};
+    use crate::file::tokens::Tokens;
+    use crate::file::TokenStore;
+    use crate::net::listener::VoteListener;
+    use crate::net::vote_handler::VoteScope;
+    use rsa::pkcs8::DecodePrivateKey;
+    use tokio::sync::watch;
+
+    const TEST_KEY: &str = include_str!("../tests/fixtures/test_private.pem");
+    const TOKEN: &str = "doctortoken";
+
+    fn test_state(address: &str) -> Arc<VoteMeState> {
+        let key = rsa::RsaPrivateKey::from_pkcs8_pem(TEST_KEY).unwrap();
+        let keys = Arc::new(KeyManager::from_key(key, KeyFormat::Pem, Duration::from_secs(3600)));
+        let tokens = Tokens {
+            fallback_to_default: false,
+            tokens: [("TestList".to_string(), TOKEN.to_string())].into(),
+        };
+        let tokens = Arc::new(TokenStore::from_tokens("tokens.toml", tokens));
+        let config = Config {
+            listeners: vec![ListenerConfig {
+                address: address.to_string(),
+                scope: VoteScope::default(),
+            }],
+            ..Config::default()
+        };
+        Arc::new(VoteMeState::new(config, keys, tokens, Arc::new(VoteService::new())))
+    }
+
+    /// A loopback address with a port nothing else is using.
+    fn free_address() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn probe_votes_reach_vote_service() {
+        let address = free_address();
+        let state = test_state(&address);
+        let emitted = Arc::new(Mutex::new(Vec::new()));
+        let seen = emitted.clone();
+        state.vote_service.on_vote(move |vote| seen.lock().unwrap().push(vote));
+
+        let (_shutdown, shutdown_rx) = watch::channel(false);
+        tokio::spawn(VoteListener::run(state.clone(), shutdown_rx));
+        for _ in 0..100 {
+            if matches!(state.listeners.lock().unwrap().get(&address), Some(ListenerStatus::Listening)) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let mut report = Report::default();
+        Doctor::probe(&state, "TestList", Some(TOKEN.to_string()), &mut report).await;
+
+        let lines: Vec<String> = report.checks.iter().map(|c| c.to_string()).collect();
+        assert_eq!(report.failed_step(), None, "{lines:#?}");
+        assert_eq!(lines.len(), 3);
+        let emitted = emitted.lock().unwrap();
+        assert_eq!(emitted.len(), 2);
+        assert!(emitted.iter().all(is_doctor_vote));
+    }
+
+    #[test]
+    fn only_awaited_doctor_votes_are_probes() {
+        let probe = VoteProbe::default();
+        let timestamp = Timestamp::from_millis(1_700_000_000_000);
+        let vote = Vote::new("TestList", DOCTOR_USERNAME, DOCTOR_ADDRESS, timestamp, VoteProtocol::V1);
+        assert!(!probe.expects(&vote));
+
+        let _arrived = probe.expect(timestamp);
+        assert!(probe.expects(&vote));
+        let real = Vote::new("TestList", "Alex", DOCTOR_ADDRESS, timestamp, VoteProtocol::V1);
+        assert!(!probe.expects(&real));
+    }
+
+    #[tokio::test]
+    async fn stopped_listener_fails_before_sending() {
+        let state = test_state(&free_address());
+        let mut report = Report::default();
+        Doctor::probe(&state, "TestList", Some(TOKEN.to_string()), &mut report).await;
+
+        assert_eq!(report.failed_step(), Some("Listener"));
+        assert!(matches!(report.checks[1].outcome, Outcome::Skipped));
+        assert!(matches!(report.checks[2].outcome, Outcome::Skipped));
+    }
+
+    #[test]
+    fn wildcard_binds_are_reached_over_loopback() {
+        assert_eq!(loopback("0.0.0.0:8192"), "127.0.0.1:8192");
+        assert_eq!(loopback("[::]:8192"), "[::1]:8192");
+        assert_eq!(loopback("10.0.0.5:8192"), "10.0.0.5:8192");
+    }
+}
@@ -0,0 +1,546 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::net::IpAddr;
+use std::path::Path;
+
+use crate::crypto::RSAIO::KeyFormat;
+use crate::net::endpoint::UNIX_PREFIX;
+use crate::net::ip_filter::{Cidr, IpFilter};
+use crate::net::vote_handler::{Protocol, VoteScope};
+
+pub const DEFAULT_CONFIG_PATH: &str = "plugins/VoteMe/Config.toml";
+
+/// One `[[listeners]]` entry.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ListenerConfig {
+    /// `0.0.0.0:8192`, `[::]:8192` or `unix:/path/to/voteme.sock`.
+    pub address: String,
+
+    /// `protocols = ["v1", "v2"]` and an optional `services = [...]` token scope.
+    #[serde(flatten)]
+    pub scope: VoteScope,
+}
+
+impl ListenerConfig {
+    pub fn is_unix(&self) -> bool {
+        self.address.starts_with(UNIX_PREFIX)
+    }
+}
+
+/// How HTTP senders prove they know the service's token.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpAuth {
+    /// `X-VoteMe-Signature`: HMAC-SHA256 of the body keyed with the token, base64 or hex.
+    #[default]
+    Hmac,
+    /// `X-VoteMe-Token`: the token itself.
+    Secret,
+}
+
+/// `[http]`: optional webhook receiver for vote lists that POST votes.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct HttpConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    #[serde(default = "default_http_address")]
+    pub address: String,
+
+    #[serde(default = "default_http_path")]
+    pub path: String,
+
+    #[serde(default = "default_http_max_body_bytes")]
+    pub max_body_bytes: usize,
+
+    #[serde(default)]
+    pub auth: HttpAuth,
+
+    /// Per-service override of `auth`, e.g. `service_auth = { TopG = "secret" }`.
+    #[serde(default)]
+    pub service_auth: BTreeMap<String, HttpAuth>,
+
+    /// `[[http.hooks]]`: extra paths handled by a site-specific adapter.
+    #[serde(default)]
+    pub hooks: Vec<HookConfig>,
+}
+
+/// One `[[http.hooks]]` entry, e.g. `path = "/hooks/topgg"`, `adapter = "topgg"`,
+/// `service = "top.gg"`. Votes are checked against `service`'s token.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct HookConfig {
+    pub path: String,
+    pub adapter: String,
+    pub service: String,
+
+    /// Adapter-specific settings, e.g. `options = { username_field = "user" }`.
+    #[serde(default)]
+    pub options: BTreeMap<String, String>,
+}
+
+impl HttpConfig {
+    pub fn auth_for(&self, service: &str) -> HttpAuth {
+        self.service_auth.get(service).copied().unwrap_or(self.auth)
+    }
+
+    pub fn hook(&self, path: &str) -> Option<&HookConfig> {
+        self.hooks.iter().find(|h| h.path == path)
+    }
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        HttpConfig {
+            enabled: false,
+            address: default_http_address(),
+            path: default_http_path(),
+            max_body_bytes: default_http_max_body_bytes(),
+            auth: HttpAuth::default(),
+            service_auth: BTreeMap::new(),
+            hooks: Vec::new(),
+        }
+    }
+}
+
+/// What a polled vote list's API answers.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PollKind {
+    /// A list of recent votes, one request per poll.
+    #[default]
+    Recent,
+    /// Whether one player has voted, one request per online player.
+    HasVoted,
+}
+
+/// One `[[pollers]]` entry: a vote list that has to be asked for votes.
+///
+/// `url`, `claim_url` and `headers` values may contain `{key}` (the API key)
+/// and, for `has_voted`, `{username}`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PollerConfig {
+    /// Identifies the provider in logs and in the poll state file.
+    pub name: String,
+    /// Service name the emitted votes carry.
+    pub service: String,
+
+    #[serde(default)]
+    pub kind: PollKind,
+
+    pub url: String,
+
+    #[serde(default)]
+    pub api_key: String,
+
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+
+    #[serde(default = "default_poll_interval_secs")]
+    pub interval_secs: u64,
+
+    /// Hours a reported vote is remembered. Recent votes older than this are
+    /// ignored, and a `has_voted` player isn't reported again before it passes.
+    #[serde(default = "default_poll_remember_hours")]
+    pub remember_hours: u64,
+
+    /// `has_voted` only: requested after a vote is reported, to mark it claimed.
+    #[serde(default)]
+    pub claim_url: Option<String>,
+
+    #[serde(default)]
+    pub fields: PollFields,
+}
+
+/// Where a poller finds things in a JSON answer. Paths are dot-separated;
+/// an empty `votes` path means the answer itself is the list.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct PollFields {
+    pub votes: String,
+    /// Unique vote ID; without one, votes are told apart by username and timestamp.
+    pub id: String,
+    pub username: String,
+    pub address: String,
+    pub timestamp: String,
+    /// `has_voted` only: the flag in a JSON answer. Plain `1` or `true` answers work too.
+    pub voted: String,
+}
+
+impl Default for PollFields {
+    fn default() -> Self {
+        PollFields {
+            votes: "votes".to_string(),
+            id: "id".to_string(),
+            username: "username".to_string(),
+            address: "address".to_string(),
+            timestamp: "timestamp".to_string(),
+            voted: "voted".to_string(),
+        }
+    }
+}
+
+/// One `[[forward]]` target: another Votifier server that gets a copy of every
+/// accepted vote, e.g. a game server behind the lobby that vote sites point at.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ForwardConfig {
+    /// Identifies the target in logs and names its queue file.
+    pub name: String,
+    /// `host:port` of the target's Votifier listener.
+    pub address: String,
+
+    #[serde(default = "default_forward_protocol")]
+    pub protocol: Protocol,
+
+    /// v1: the target's public key as one base64 line, as vote sites take it.
+    #[serde(default)]
+    pub public_key: String,
+
+    /// v2: the target's token for the services forwarded to it.
+    #[serde(default)]
+    pub token: String,
+
+    /// Services to forward; empty forwards all.
+    #[serde(default)]
+    pub services: Vec<String>,
+
+    /// Delay before the first retry; it doubles per failure up to `retry_max_secs`.
+    #[serde(default = "default_forward_retry_min_secs")]
+    pub retry_min_secs: u64,
+
+    #[serde(default = "default_forward_retry_max_secs")]
+    pub retry_max_secs: u64,
+
+    /// Votes kept while the target is down; the oldest are dropped past this.
+    #[serde(default = "default_forward_max_queue")]
+    pub max_queue: usize,
+}
+
+impl ForwardConfig {
+    pub fn forwards(&self, service: &str) -> bool {
+        self.services.is_empty() || self.services.iter().any(|s| s == service)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Config {
+    /// Used when no `[[listeners]]` are configured.
+    #[serde(default = "default_host")]
+    pub host: String,
+
+    #[serde(default = "default_port")]
+    pub port: u16,
+
+    #[serde(default = "default_rsa_bits")]
+    pub rsa_bits: u32,
+
+    #[serde(default = "default_debug")]
+    pub debug: bool,
+
+    /// Format for newly written key files: `pem` or `votifier` (base64 DER).
+    /// Existing key files are read in either format.
+    #[serde(default)]
+    pub key_format: KeyFormat,
+
+    /// Hours a rotated-out key keeps decrypting votes.
+    #[serde(default = "default_key_grace_hours")]
+    pub key_grace_hours: u64,
+
+    /// Environment variable holding the private key passphrase. When set, the
+    /// private key is stored as encrypted PKCS#8.
+    #[serde(default = "default_key_passphrase_env")]
+    pub key_passphrase_env: String,
+
+    /// File holding the passphrase, used when the environment variable is unset.
+    /// Keep it outside the plugin folder.
+    #[serde(default)]
+    pub key_passphrase_file: Option<String>,
+
+    /// Reload automatically when `Config.toml` or `tokens.toml` change on disk.
+    #[serde(default = "default_watch_files")]
+    pub watch_files: bool,
+
+    /// Seconds to let in-flight votes finish when the plugin unloads.
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub shutdown_timeout_secs: u64,
+
+    /// Seconds a sender gets to deliver its whole vote before the connection is closed.
+    #[serde(default = "default_read_timeout_secs")]
+    pub read_timeout_secs: u64,
+
+    /// Connections handled at once; further connections are closed on accept.
+    #[serde(default = "default_max_connections")]
+    pub max_connections: u64,
+
+    /// Connections per minute allowed from one IP; `0` disables the limit.
+    #[serde(default = "default_rate_limit_per_minute")]
+    pub rate_limit_per_minute: u32,
+
+    /// Connections one IP may open back to back before the per-minute rate applies.
+    #[serde(default = "default_rate_limit_burst")]
+    pub rate_limit_burst: u32,
+
+    /// Invalid packets in a row before an IP is banned; `0` disables bans.
+    #[serde(default = "default_ban_after_invalid")]
+    pub ban_after_invalid: u32,
+
+    /// Minutes a banned IP stays banned.
+    #[serde(default = "default_ban_minutes")]
+    pub ban_minutes: u64,
+
+    /// Allow and deny lists of IPs and CIDR ranges, globally and per service.
+    #[serde(default)]
+    pub ip_filter: IpFilter,
+
+    /// Expect a HAProxy PROXY protocol (v1 or v2) header from `trusted_proxies`
+    /// and use the client address it carries instead of the proxy's.
+    #[serde(default)]
+    pub proxy_protocol: bool,
+
+    /// Proxies allowed to send a PROXY header. Connections from these addresses
+    /// must start with one; other peers are treated as direct connections.
+    /// HTTP requests from these addresses are checked against the client in
+    /// their `Forwarded` or `X-Forwarded-For` header instead.
+    #[serde(default)]
+    pub trusted_proxies: Vec<Cidr>,
+
+    /// Addresses to accept votes on, each with its own protocols and services.
+    /// Empty means a single listener on `host:port` for both protocols.
+    #[serde(default)]
+    pub listeners: Vec<ListenerConfig>,
+
+    /// Seconds during which a vote with the same service, username and timestamp
+    /// is dropped as a duplicate; `0` disables the check. Webhook votes that carry
+    /// no timestamp are matched on service and username alone.
+    #[serde(default = "default_dedupe_window_secs")]
+    pub dedupe_window_secs: u64,
+
+    #[serde(default)]
+    pub http: HttpConfig,
+
+    /// Vote lists that don't push votes and are polled instead.
+    #[serde(default)]
+    pub pollers: Vec<PollerConfig>,
+
+    /// Downstream servers every accepted vote is re-sent to.
+    #[serde(default)]
+    pub forward: Vec<ForwardConfig>,
+}
+
+impl Config {
+    pub fn bind_addr(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    /// Address the HTTP receiver should be bound to, if it's enabled.
+    pub fn http_addr(&self) -> Option<String> {
+        self.http.enabled.then(|| self.http.address.clone())
+    }
+
+    /// The configured listeners, or the `host:port` fallback.
+    pub fn listeners(&self) -> Vec<ListenerConfig> {
+        if !self.listeners.is_empty() {
+            return self.listeners.clone();
+        }
+        vec![ListenerConfig {
+            address: self.bind_addr(),
+            scope: VoteScope::default(),
+        }]
+    }
+
+    /// Whether connections from `ip` start with a PROXY protocol header.
+    pub fn is_trusted_proxy(&self, ip: IpAddr) -> bool {
+        self.proxy_protocol && self.trusts_proxy(ip)
+    }
+
+    /// Whether `ip` is in `trusted_proxies`, whose forwarding headers are believed.
+    pub fn trusts_proxy(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.trusted_proxies.iter().any(|range| range.contains(ip))
+    }
+}
+
+fn default_host() -> String {
+    "0.0.0.0".to_string()
+}
+
+fn default_port() -> u16 {
+    8192
+}
+
+fn default_rsa_bits() -> u32 {
+    2048
+}
+
+fn default_debug() -> bool {
+    false
+}
+
+fn default_key_grace_hours() -> u64 {
+    72
+}
+
+fn default_key_passphrase_env() -> String {
+    "VOTEME_KEY_PASSPHRASE".to_string()
+}
+
+fn default_watch_files() -> bool {
+    false
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    5
+}
+
+fn default_read_timeout_secs() -> u64 {
+    10
+}
+
+fn default_max_connections() -> u64 {
+    256
+}
+
+fn default_rate_limit_per_minute() -> u32 {
+    30
+}
+
+fn default_rate_limit_burst() -> u32 {
+    10
+}
+
+fn default_ban_after_invalid() -> u32 {
+    5
+}
+
+fn default_ban_minutes() -> u64 {
+    15
+}
+
+fn default_dedupe_window_secs() -> u64 {
+    600
+}
+
+fn default_http_address() -> String {
+    "0.0.0.0:8193".to_string()
+}
+
+fn default_http_path() -> String {
+    "/vote".to_string()
+}
+
+fn default_http_max_body_bytes() -> usize {
+    16 * 1024
+}
+
+fn default_poll_interval_secs() -> u64 {
+    60
+}
+
+fn default_poll_remember_hours() -> u64 {
+    24
+}
+
+fn default_forward_protocol() -> Protocol {
+    Protocol::V2
+}
+
+fn default_forward_retry_min_secs() -> u64 {
+    5
+}
+
+fn default_forward_retry_max_secs() -> u64 {
+    600
+}
+
+fn default_forward_max_queue() -> usize {
+    10_000
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            host: default_host(),
+            port: default_port(),
+            rsa_bits: default_rsa_bits(),
+            debug: default_debug(),
+            key_format: KeyFormat::default(),
+            key_grace_hours: default_key_grace_hours(),
+            key_passphrase_env: default_key_passphrase_env(),
+            key_passphrase_file: None,
+            watch_files: default_watch_files(),
+            shutdown_timeout_secs: default_shutdown_timeout_secs(),
+            read_timeout_secs: default_read_timeout_secs(),
+            max_connections: default_max_connections(),
+            rate_limit_per_minute: default_rate_limit_per_minute(),
+            rate_limit_burst: default_rate_limit_burst(),
+            ban_after_invalid: default_ban_after_invalid(),
+            ban_minutes: default_ban_minutes(),
+            ip_filter: IpFilter::default(),
+            proxy_protocol: false,
+            trusted_proxies: Vec::new(),
+            listeners: Vec::new(),
+            dedupe_window_secs: default_dedupe_window_secs(),
+            http: HttpConfig::default(),
+            pollers: Vec::new(),
+            forward: Vec::new(),
+        }
+    }
+}
+
+pub struct ConfigManager {
+    config_file: String,
+}
+
+impl ConfigManager {
+    pub fn new_default() -> Self {
+        Self::new(DEFAULT_CONFIG_PATH)
+    }
+
+    pub fn new(config_file: &str) -> Self {
+        ConfigManager {
+            config_file: config_file.to_string(),
+        }
+    }
+
+    pub async fn init_config(&mut self, config: &mut Config) -> Result<(), String> {
+        let config_path = Path::new(&self.config_file);
+
+        // Create parent directories if they don't exist
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+
+        if !config_path.exists() {
+            self.save_config(config).await?;
+            log::info!("Created new config file at: {}", self.config_file);
+        } else {
+            self.load_config(config).await?;
+            log::info!("Loaded config file from: {}", self.config_file);
+        }
+        Ok(())
+    }
+
+    pub async fn load_config(&self, config: &mut Config) -> Result<(), String> {
+        let mut file = std::fs::File::open(&self.config_file).map_err(|e| e.to_string())?;
+        let mut config_str = String::new();
+        file.read_to_string(&mut config_str)
+            .map_err(|e| e.to_string())?;
+        *config = toml::from_str(&config_str).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub async fn save_config(&self, config: &Config) -> Result<(), String> {
+        let config_path = Path::new(&self.config_file);
+
+        // Create parent directories if they don't exist
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+
+        let mut file = std::fs::File::create(&self.config_file).map_err(|e| e.to_string())?;
+        let config_str = toml::to_string_pretty(config).map_err(|e| e.to_string())?;
+        file.write_all(config_str.as_bytes()).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::RwLock;
+
+use crate::crypto::HMAC;
+use crate::file;
+
+pub const DEFAULT_TOKENS_PATH: &str = "plugins/VoteMe/tokens.toml";
+pub const DEFAULT_SERVICE: &str = "default";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Tokens {
+    /// Use the `default` token for services that have no token of their own.
+    #[serde(default)]
+    pub fallback_to_default: bool,
+
+    #[serde(default)]
+    pub tokens: BTreeMap<String, String>,
+}
+
+/// Votifier v2 shared secrets by service name, persisted to `tokens.toml`.
+pub struct TokenStore {
+    tokens_file: String,
+    inner: RwLock<Tokens>,
+}
+
+impl TokenStore {
+    pub fn new_default() -> Self {
+        Self::new(DEFAULT_TOKENS_PATH)
+    }
+
+    pub fn new(tokens_file: &str) -> Self {
+        Self::from_tokens(tokens_file, Tokens::default())
+    }
+
+    pub fn from_tokens(tokens_file: &str, tokens: Tokens) -> Self {
+        TokenStore {
+            tokens_file: tokens_file.to_string(),
+            inner: RwLock::new(tokens),
+        }
+    }
+
+    /// Loads the token file, creating it with a fresh `default` token on first start.
+    pub fn init(&self) -> Result<(), String> {
+        let tokens_path = Path::new(&self.tokens_file);
+
+        if tokens_path.exists() {
+            self.load()?;
+            log::info!("Loaded tokens file from: {}", self.tokens_file);
+        }
+
+        let created = {
+            let mut inner = self.write()?;
+            if inner.tokens.contains_key(DEFAULT_SERVICE) {
+                false
+            } else {
+                inner.tokens.insert(DEFAULT_SERVICE.to_string(), HMAC::random_string());
+                true
+            }
+        };
+
+        if created {
+            self.save()?;
+            log::info!("Generated default v2 token in: {}", self.tokens_file);
+        }
+        Ok(())
+    }
+
+    pub fn load(&self) -> Result<(), String> {
+        let tokens = self.read_file()?;
+        *self.write()? = tokens;
+        Ok(())
+    }
+
+    /// Parses the token file without replacing the tokens in use.
+    pub fn read_file(&self) -> Result<Tokens, String> {
+        let tokens_str = fs::read_to_string(&self.tokens_file)
+            .map_err(|e| format!("Failed to read tokens file {}: {e}", self.tokens_file))?;
+        toml::from_str(&tokens_str).map_err(|e| format!("Invalid tokens file {}: {e}", self.tokens_file))
+    }
+
+    /// Writes the tokens readable only by the owner, like the key files.
+    pub fn save(&self) -> Result<(), String> {
+        let tokens_str = {
+            let inner = self.read()?;
+            toml::to_string_pretty(&*inner).map_err(|e| e.to_string())?
+        };
+        file::write_private(Path::new(&self.tokens_file), tokens_str.as_bytes())
+            .map_err(|e| format!("Failed to write tokens file {}: {e}", self.tokens_file))
+    }
+
+    /// Token used to verify votes from `service`.
+    pub fn get(&self, service: &str) -> Option<String> {
+        let inner = self.inner.read().ok()?;
+        match inner.tokens.get(service) {
+            Some(token) => Some(token.clone()),
+            None if inner.fallback_to_default => inner.tokens.get(DEFAULT_SERVICE).cloned(),
+            None => None,
+        }
+    }
+
+    pub fn services(&self) -> Vec<String> {
+        self.inner
+            .read()
+            .map(|inner| inner.tokens.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Adds a token for `service`, generating one when `token` is `None`.
+    /// Fails if the service already has a token; use `rotate` to replace it.
+    pub fn add(&self, service: &str, token: Option<String>) -> Result<String, String> {
+        let token = token.unwrap_or_else(HMAC::random_string);
+        {
+            let mut inner = self.write()?;
+            if inner.tokens.contains_key(service) {
+                return Err(format!("Service {service} already has a token"));
+            }
+            inner.tokens.insert(service.to_string(), token.clone());
+        }
+        self.save()?;
+        log::info!("Added v2 token for service {}", service);
+        Ok(token)
+    }
+
+    /// Replaces the token of `service` with a freshly generated one.
+    pub fn rotate(&self, service: &str) -> Result<String, String> {
+        let token = HMAC::random_string();
+        {
+            let mut inner = self.write()?;
+            match inner.tokens.get_mut(service) {
+                Some(existing) => *existing = token.clone(),
+                None => return Err(format!("Service {service} has no token")),
+            }
+        }
+        self.save()?;
+        log::info!("Rotated v2 token for service {}", service);
+        Ok(token)
+    }
+
+    /// Removes the token of `service`. Returns `false` if there was none.
+    pub fn revoke(&self, service: &str) -> Result<bool, String> {
+        let removed = self.write()?.tokens.remove(service).is_some();
+        if removed {
+            self.save()?;
+            log::info!("Revoked v2 token for service {}", service);
+        }
+        Ok(removed)
+    }
+
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, Tokens>, String> {
+        self.inner.read().map_err(|_| "Token store lock poisoned".to_string())
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, Tokens>, String> {
+        self.inner.write().map_err(|_| "Token store lock poisoned".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(name: &str) -> (TokenStore, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("voteme-tokens-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("tokens.toml");
+        (TokenStore::new(path.to_str().unwrap()), dir)
+    }
+
+    #[test]
+    fn first_start_generates_a_default_token_that_survives_a_reload() {
+        let (store, dir) = temp_store("init");
+        store.init().unwrap();
+        let default = store.get(DEFAULT_SERVICE).unwrap();
+        assert!(!default.is_empty());
+
+        let reloaded = TokenStore::new(&store.tokens_file);
+        reloaded.init().unwrap();
+        assert_eq!(reloaded.get(DEFAULT_SERVICE), Some(default));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&store.tokens_file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn add_rotate_and_revoke() {
+        let (store, dir) = temp_store("manage");
+        assert_eq!(store.add("TestList", Some("secret".to_string())).unwrap(), "secret");
+        assert!(store.add("TestList", None).is_err());
+
+        let rotated = store.rotate("TestList").unwrap();
+        assert_ne!(rotated, "secret");
+        assert!(store.rotate("Unknown").is_err());
+
+        store.load().unwrap();
+        assert_eq!(store.get("TestList"), Some(rotated));
+
+        assert!(store.revoke("TestList").unwrap());
+        assert!(!store.revoke("TestList").unwrap());
+        store.load().unwrap();
+        assert_eq!(store.get("TestList"), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unknown_services_fall_back_only_when_configured() {
+        let store = TokenStore::new("tokens.toml");
+        *store.inner.write().unwrap() = Tokens {
+            fallback_to_default: false,
+            tokens: [(DEFAULT_SERVICE.to_string(), "fallback".to_string())].into(),
+        };
+        assert_eq!(store.get("TestList"), None);
+
+        store.inner.write().unwrap().fallback_to_default = true;
+        assert_eq!(store.get("TestList").as_deref(), Some("fallback"));
+    }
+}
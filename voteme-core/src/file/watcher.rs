@@ -0,0 +1,39 @@
+use std::fs;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use crate::file::config::DEFAULT_CONFIG_PATH;
+use crate::file::tokens::DEFAULT_TOKENS_PATH;
+use crate::state::VoteMeState;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+fn modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Polls `Config.toml` and `tokens.toml` and reloads when either changes.
+/// Does nothing while `watch_files` is off; the flag itself is re-read every poll.
+pub async fn watch_files(state: Arc<VoteMeState>) {
+    let paths = [DEFAULT_CONFIG_PATH, DEFAULT_TOKENS_PATH];
+    let mut last: Vec<_> = paths.iter().map(|p| modified(p)).collect();
+
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let current: Vec<_> = paths.iter().map(|p| modified(p)).collect();
+        if current == last {
+            continue;
+        }
+        last = current;
+
+        if !state.config.read().unwrap().watch_files {
+            continue;
+        }
+
+        log::info!("Config files changed on disk, reloading...");
+        if let Err(e) = state.reload().await {
+            log::error!("Reload failed: {}", e);
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! Proxy mode: every accepted vote is re-sent to each `[[forward]]` target,
+//! through a queue per target that outlives restarts.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, watch, Notify};
+use tokio::task::JoinSet;
+use voteme_api::{Vote, VoteService};
+
+use crate::doctor;
+use crate::file::config::ForwardConfig;
+use crate::net::vote_handler::Protocol;
+use crate::parser::vote_parser::now_millis;
+use crate::state::{ForwardStatus, VoteMeState, VoteStats};
+
+pub mod queue;
+pub mod sender;
+
+use queue::ForwardQueue;
+use sender::Credentials;
+
+pub const FORWARD_DIR: &str = "plugins/VoteMe/forward";
+
+/// A target's queue and the signal that wakes its sender.
+struct Target {
+    config: Arc<ForwardConfig>,
+    queue: Arc<ForwardQueue>,
+    wake: Arc<Notify>,
+}
+
+pub struct Forwarder;
+
+impl Forwarder {
+    /// Listens on `service` for emitted votes; the receiver feeds [`Forwarder::run`].
+    pub fn subscribe(service: &VoteService) -> mpsc::UnboundedReceiver<Vote> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        service.on_vote(move |vote| {
+            if doctor::is_doctor_vote(&vote) {
+                return;
+            }
+            let _ = tx.send(vote);
+        });
+        rx
+    }
+
+    /// Queues votes for the configured targets, with one sender task per target.
+    /// Changing `forward` restarts the senders; queued votes stay on disk.
+    pub async fn run(
+        state: Arc<VoteMeState>,
+        mut votes: mpsc::UnboundedReceiver<Vote>,
+        mut shutdown: watch::Receiver<bool>,
+    ) {
+        let mut configs_rx = state.subscribe_forwarders();
+        let mut senders = JoinSet::new();
+
+        loop {
+            let configs = configs_rx.borrow_and_update().clone();
+            let targets = Self::open(&state, configs, &mut senders);
+
+            loop {
+                tokio::select! {
+                    _ = shutdown.changed() => {
+                        senders.shutdown().await;
+                        return;
+                    }
+                    changed = configs_rx.changed() => {
+                        if changed.is_err() {
+                            senders.shutdown().await;
+                            return;
+                        }
+                        break;
+                    }
+                    Some(vote) = votes.recv() => {
+                        for target in targets.iter().filter(|t| t.config.forwards(&vote.service_name)) {
+                            match target.queue.push(&vote) {
+                                Ok(()) => target.wake.notify_one(),
+                                Err(e) => log::error!("Could not queue vote for {}: {}", target.config.name, e),
+                            }
+                        }
+                    }
+                }
+            }
+
+            senders.shutdown().await;
+        }
+    }
+
+    fn open(state: &Arc<VoteMeState>, configs: Vec<ForwardConfig>, senders: &mut JoinSet<()>) -> Vec<Target> {
+        state.forwarders.lock().unwrap().clear();
+        let mut targets = Vec::new();
+
+        for config in configs {
+            let queue_file = format!("{}/{}.json", FORWARD_DIR, config.name);
+            let queue = match ForwardQueue::open(&queue_file, config.max_queue) {
+                Ok(queue) => Arc::new(queue),
+                Err(e) => {
+                    log::error!("Forwarding to {} disabled: {}", config.name, e);
+                    state.set_forward_status(&config.name, ForwardStatus::failed(0, e));
+                    continue;
+                }
+            };
+            let target = Target {
+                config: Arc::new(config),
+                queue,
+                wake: Arc::new(Notify::new()),
+            };
+
+            // Votes still queue up while credentials are wrong, and go out once they're fixed.
+            match credentials(&target.config) {
+                Ok(credentials) => {
+                    log::info!(
+                        "Forwarding votes to {} ({}, {}), {} queued",
+                        target.config.name,
+                        target.config.address,
+                        target.config.protocol,
+                        target.queue.len()
+                    );
+                    senders.spawn(Self::deliver(
+                        state.clone(),
+                        target.config.clone(),
+                        target.queue.clone(),
+                        target.wake.clone(),
+                        credentials,
+                    ));
+                }
+                Err(e) => {
+                    log::error!("Forwarding to {} paused: {}", target.config.name, e);
+                    state.set_forward_status(&target.config.name, ForwardStatus::failed(target.queue.len(), e));
+                }
+            }
+            targets.push(target);
+        }
+        targets
+    }
+
+    /// Sends the queue oldest first. A connection failure backs off before the same
+    /// vote is retried; a vote the target refuses or can't read is dropped.
+    async fn deliver(
+        state: Arc<VoteMeState>,
+        config: Arc<ForwardConfig>,
+        queue: Arc<ForwardQueue>,
+        wake: Arc<Notify>,
+        credentials: Credentials,
+    ) {
+        let retry_min = Duration::from_secs(config.retry_min_secs.max(1));
+        let retry_max = Duration::from_secs(config.retry_max_secs).max(retry_min);
+        let mut last_error = None;
+
+        loop {
+            state.set_forward_status(
+                &config.name,
+                ForwardStatus {
+                    queued: queue.len(),
+                    last_error: last_error.clone(),
+                },
+            );
+
+            let Some(entry) = queue.front() else {
+                wake.notified().await;
+                continue;
+            };
+            let wait = entry.next_attempt - now_millis();
+            if wait > 0 {
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_millis(wait as u64)) => {}
+                    _ = wake.notified() => {}
+                }
+                continue;
+            }
+
+            let timeout = Duration::from_secs(state.config.read().unwrap().read_timeout_secs);
+            let result = sender::send(&config.address, &credentials, &entry.vote, timeout)
+                .await
+                .and_then(|exchange| exchange.outcome());
+            let saved = match result {
+                Ok(()) => {
+                    VoteStats::inc(&state.stats.forwarded);
+                    last_error = None;
+                    queue.remove(entry.id)
+                }
+                Err(e) if !e.is_transient() => {
+                    VoteStats::inc(&state.stats.forward_failures);
+                    log::error!(
+                        "Dropped the vote of {} from {} for {}: {}",
+                        entry.vote.username,
+                        entry.vote.service_name,
+                        config.name,
+                        e
+                    );
+                    last_error = Some(e.to_string());
+                    queue.remove(entry.id)
+                }
+                Err(e) => {
+                    VoteStats::inc(&state.stats.forward_failures);
+                    let retry = queue.retry_later(entry.id, now_millis(), retry_min, retry_max);
+                    if let Ok((attempts, delay)) = &retry {
+                        log::warn!(
+                            "Forwarding the vote of {} to {} failed ({} attempts): {}; retrying in {}s",
+                            entry.vote.username,
+                            config.name,
+                            attempts,
+                            e,
+                            delay.as_secs()
+                        );
+                    }
+                    last_error = Some(e.to_string());
+                    retry.map(drop)
+                }
+            };
+            if let Err(e) = saved {
+                // The in-memory queue is still right; the file catches up on the next change.
+                log::error!("{}", e);
+            }
+        }
+    }
+}
+
+fn credentials(config: &ForwardConfig) -> Result<Credentials, String> {
+    match config.protocol {
+        Protocol::V1 => sender::parse_public_key(&config.public_key)
+            .map(Credentials::V1)
+            .map_err(|e| e.to_string()),
+        Protocol::V2 if config.token.is_empty() => Err("v2 forwarding needs a token".to_string()),
+        Protocol::V2 => Ok(Credentials::V2(config.token.clone())),
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use voteme_api::Vote;
+
+/// A vote waiting for its target, as stored in the queue file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedVote {
+    /// Names the entry while it's being sent, since a full queue can drop the
+    /// front in the meantime. Handed out again on every open.
+    #[serde(skip)]
+    pub id: u64,
+    #[serde(flatten)]
+    pub vote: Vote,
+    pub attempts: u32,
+    /// Unix milliseconds before which it isn't retried.
+    pub next_attempt: i64,
+}
+
+/// One target's undelivered votes, oldest first. Every change is written to
+/// the queue file, so votes survive a restart of either side.
+pub struct ForwardQueue {
+    queue_file: String,
+    max_len: usize,
+    entries: Mutex<Entries>,
+}
+
+struct Entries {
+    votes: VecDeque<QueuedVote>,
+    next_id: u64,
+}
+
+impl ForwardQueue {
+    /// Opens the queue, picking up whatever the file still holds.
+    pub fn open(queue_file: &str, max_len: usize) -> Result<Self, String> {
+        let mut votes: VecDeque<QueuedVote> = if Path::new(queue_file).exists() {
+            let queue_str = fs::read_to_string(queue_file)
+                .map_err(|e| format!("Failed to read forward queue {queue_file}: {e}"))?;
+            serde_json::from_str(&queue_str).map_err(|e| format!("Invalid forward queue {queue_file}: {e}"))?
+        } else {
+            VecDeque::new()
+        };
+        for (id, entry) in votes.iter_mut().enumerate() {
+            entry.id = id as u64;
+        }
+
+        Ok(ForwardQueue {
+            queue_file: queue_file.to_string(),
+            max_len: max_len.max(1),
+            entries: Mutex::new(Entries {
+                next_id: votes.len() as u64,
+                votes,
+            }),
+        })
+    }
+
+    /// Appends `vote`, dropping the oldest entry once `max_len` is reached.
+    pub fn push(&self, vote: &Vote) -> Result<(), String> {
+        let mut entries = self.entries.lock().unwrap();
+        while entries.votes.len() >= self.max_len {
+            if let Some(dropped) = entries.votes.pop_front() {
+                log::warn!(
+                    "Forward queue {} is full; dropped the vote of {} from {}",
+                    self.queue_file,
+                    dropped.vote.username,
+                    dropped.vote.service_name
+                );
+            }
+        }
+        let id = entries.next_id;
+        entries.next_id += 1;
+        entries.votes.push_back(QueuedVote {
+            id,
+            vote: vote.clone(),
+            attempts: 0,
+            next_attempt: 0,
+        });
+        self.save(&entries.votes)
+    }
+
+    pub fn front(&self) -> Option<QueuedVote> {
+        self.entries.lock().unwrap().votes.front().cloned()
+    }
+
+    /// Removes entry `id` once it's been delivered or given up on. Does nothing
+    /// if a full queue already dropped it.
+    pub fn remove(&self, id: u64) -> Result<(), String> {
+        let mut entries = self.entries.lock().unwrap();
+        let Some(index) = entries.votes.iter().position(|entry| entry.id == id) else {
+            return Ok(());
+        };
+        entries.votes.remove(index);
+        self.save(&entries.votes)
+    }
+
+    /// Counts a failed attempt on entry `id` and schedules the next one.
+    pub fn retry_later(&self, id: u64, now: i64, min: Duration, max: Duration) -> Result<(u32, Duration), String> {
+        let mut entries = self.entries.lock().unwrap();
+        let Some(entry) = entries.votes.iter_mut().find(|entry| entry.id == id) else {
+            return Ok((0, Duration::ZERO));
+        };
+        entry.attempts += 1;
+        let delay = backoff(entry.attempts, min, max);
+        entry.next_attempt = now + delay.as_millis() as i64;
+        let attempts = entry.attempts;
+        self.save(&entries.votes)?;
+        Ok((attempts, delay))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().votes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Writes to a temporary file first so a crash mid-write keeps the old queue.
+    fn save(&self, entries: &VecDeque<QueuedVote>) -> Result<(), String> {
+        let queue_path = Path::new(&self.queue_file);
+
+        if let Some(parent) = queue_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+
+        let queue_str = serde_json::to_string(entries).map_err(|e| e.to_string())?;
+        let temp_path = queue_path.with_extension("json.tmp");
+        fs::write(&temp_path, queue_str)
+            .and_then(|_| fs::rename(&temp_path, queue_path))
+            .map_err(|e| format!("Failed to write forward queue {}: {e}", self.queue_file))
+    }
+}
+
+/// `min` doubled per failed attempt after the first, capped at `max`.
+pub fn backoff(attempts: u32, min: Duration, max: Duration) -> Duration {
+    let factor = 1u32.checked_shl(attempts.saturating_sub(1)).unwrap_or(u32::MAX);
+    min.saturating_mul(factor).min(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use voteme_api::{Timestamp, VoteProtocol};
+
+    fn vote(username: &str) -> Vote {
+        Vote::new("TestList", username, "", Timestamp::from_millis(1_700_000_000_000), VoteProtocol::V2)
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let (min, max) = (Duration::from_secs(5), Duration::from_secs(60));
+        assert_eq!(backoff(1, min, max), Duration::from_secs(5));
+        assert_eq!(backoff(3, min, max), Duration::from_secs(20));
+        assert_eq!(backoff(5, min, max), Duration::from_secs(60));
+        assert_eq!(backoff(200, min, max), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn queue_survives_reopening_and_drops_the_oldest() {
+        let queue_file = std::env::temp_dir().join(format!("voteme-forward-{}.json", std::process::id()));
+        let queue_file = queue_file.to_str().unwrap();
+
+        let queue = ForwardQueue::open(queue_file, 2).unwrap();
+        for name in ["Alex", "Steve", "Notch"] {
+            queue.push(&vote(name)).unwrap();
+        }
+        let steve = queue.front().unwrap().id;
+        let (attempts, _) = queue
+            .retry_later(steve, 0, Duration::from_secs(5), Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(attempts, 1);
+
+        let reopened = ForwardQueue::open(queue_file, 2).unwrap();
+        assert_eq!(reopened.len(), 2);
+        let front = reopened.front().unwrap();
+        assert_eq!(front.vote.username, "Steve");
+        assert_eq!(front.next_attempt, 5000);
+
+        reopened.remove(front.id).unwrap();
+        assert_eq!(reopened.front().unwrap().vote.username, "Notch");
+
+        fs::remove_file(queue_file).unwrap();
+    }
+
+    #[test]
+    fn dropping_the_sent_entry_leaves_the_next_one_alone() {
+        let queue_file = std::env::temp_dir().join(format!("voteme-forward-full-{}.json", std::process::id()));
+        let queue_file = queue_file.to_str().unwrap();
+
+        let queue = ForwardQueue::open(queue_file, 2).unwrap();
+        queue.push(&vote("Alex")).unwrap();
+        queue.push(&vote("Steve")).unwrap();
+        let sending = queue.front().unwrap();
+
+        // Alex is dropped for Notch while the send is in flight.
+        queue.push(&vote("Notch")).unwrap();
+        let (attempts, _) = queue
+            .retry_later(sending.id, 0, Duration::from_secs(5), Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(attempts, 0);
+        queue.remove(sending.id).unwrap();
+
+        assert_eq!(queue.len(), 2);
+        let front = queue.front().unwrap();
+        assert_eq!(front.vote.username, "Steve");
+        assert_eq!(front.attempts, 0);
+
+        fs::remove_file(queue_file).unwrap();
+    }
+}
@@ -0,0 +1,333 @@
+use std::time::Duration;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rsa::pkcs8::DecodePublicKey;
+use rsa::RsaPublicKey;
+use serde::Deserialize;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use voteme_api::Vote;
+
+use crate::crypto::{CryptoError, HMAC, RSA, RSAIO};
+use crate::net::vote_handler::V2_MAGIC;
+
+/// Longest greeting or status line read from the server.
+const MAX_LINE: u64 = 1024;
+
+/// What the server needs to accept a vote from us.
+pub enum Credentials {
+    /// The server's public key, for an RSA-encrypted v1 block.
+    V1(RsaPublicKey),
+    /// The server's token for the vote's service, for a signed v2 message.
+    V2(String),
+}
+
+/// Everything said during one delivery.
+#[derive(Debug, Clone)]
+pub struct Exchange {
+    /// The `VOTIFIER <version> [challenge]` line the server opened with.
+    pub greeting: String,
+    /// Bytes of vote data written.
+    pub sent: usize,
+    /// The v2 status line; v1 servers never answer.
+    pub answer: Option<String>,
+}
+
+impl Exchange {
+    /// Whether the server took the vote, as far as it said.
+    pub fn outcome(&self) -> Result<(), SendError> {
+        match &self.answer {
+            Some(answer) => check_answer(answer),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SendError {
+    Io(std::io::Error),
+    Timeout,
+    /// The server said something that isn't Votifier.
+    Protocol(String),
+    /// A v2 server answered with an error status.
+    Rejected { cause: String, error: String },
+    Crypto(CryptoError),
+}
+
+impl SendError {
+    /// Whether sending the same vote again later can succeed. A rejected vote or
+    /// one the server can't read fails the same way every time.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, SendError::Io(_) | SendError::Timeout)
+    }
+}
+
+impl std::fmt::Display for SendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SendError::Io(e) => write!(f, "I/O error: {e}"),
+            SendError::Timeout => write!(f, "timed out"),
+            SendError::Protocol(msg) => write!(f, "protocol error: {msg}"),
+            SendError::Rejected { cause, error } => write!(f, "rejected ({cause}): {error}"),
+            SendError::Crypto(e) => write!(f, "crypto error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SendError {}
+
+impl From<std::io::Error> for SendError {
+    fn from(value: std::io::Error) -> Self {
+        SendError::Io(value)
+    }
+}
+
+impl From<CryptoError> for SendError {
+    fn from(value: CryptoError) -> Self {
+        SendError::Crypto(value)
+    }
+}
+
+/// Connects to `address` (`host:port`) and delivers `vote`, all within `timeout`.
+pub async fn send(
+    address: &str,
+    credentials: &Credentials,
+    vote: &Vote,
+    timeout: Duration,
+) -> Result<Exchange, SendError> {
+    tokio::time::timeout(timeout, async {
+        let stream = TcpStream::connect(address).await?;
+        send_over(stream, credentials, vote).await
+    })
+    .await
+    .map_err(|_| SendError::Timeout)?
+}
+
+/// Reads the greeting, writes `vote` and, for v2, reads the server's answer.
+pub async fn send_over<S>(stream: S, credentials: &Credentials, vote: &Vote) -> Result<Exchange, SendError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut stream = BufReader::new(stream);
+    let greeting = read_line(&mut stream).await?;
+    let mut parts = greeting.split_whitespace();
+    if parts.next() != Some("VOTIFIER") {
+        return Err(SendError::Protocol(format!("unexpected greeting {greeting:?}")));
+    }
+
+    let packet = match credentials {
+        Credentials::V1(key) => encode_v1(vote, key)?,
+        Credentials::V2(token) => {
+            let _version = parts.next();
+            let challenge = parts
+                .next()
+                .ok_or_else(|| SendError::Protocol(format!("server does not speak v2: {greeting:?}")))?;
+            encode_v2(vote, token, challenge)?
+        }
+    };
+    stream.write_all(&packet).await?;
+    stream.flush().await?;
+
+    let answer = match credentials {
+        Credentials::V1(_) => None,
+        Credentials::V2(_) => Some(read_line(&mut stream).await?),
+    };
+
+    Ok(Exchange {
+        greeting,
+        sent: packet.len(),
+        answer,
+    })
+}
+
+/// The `VOTE` block, RSA-encrypted with the server's key.
+pub fn encode_v1(vote: &Vote, key: &RsaPublicKey) -> Result<Vec<u8>, SendError> {
+    let plaintext = format!(
+        "VOTE\n{}\n{}\n{}\n{}\n",
+        vote.service_name, vote.username, vote.address, vote.timestamp
+    );
+    Ok(RSA::encrypt(plaintext.as_bytes(), key)?)
+}
+
+/// Magic, length, then the `{"payload", "signature"}` message signed with `token`.
+pub fn encode_v2(vote: &Vote, token: &str, challenge: &str) -> Result<Vec<u8>, SendError> {
+    let mut payload = serde_json::json!({
+        "serviceName": vote.service_name,
+        "username": vote.username,
+        "address": vote.address,
+        "timestamp": vote.timestamp.as_millis(),
+        "challenge": challenge,
+    });
+    if !vote.additional_data.is_empty() {
+        payload["additionalData"] = vote.additional_data.clone().into();
+    }
+    let payload = payload.to_string();
+    let signature = STANDARD.encode(HMAC::sign(payload.as_bytes(), token));
+    let message = serde_json::json!({ "payload": payload, "signature": signature }).to_string();
+
+    let len = u16::try_from(message.len()).map_err(|_| SendError::Protocol("vote too large for v2".to_string()))?;
+    let mut packet = Vec::with_capacity(message.len() + 4);
+    packet.extend_from_slice(&V2_MAGIC.to_be_bytes());
+    packet.extend_from_slice(&len.to_be_bytes());
+    packet.extend_from_slice(message.as_bytes());
+    Ok(packet)
+}
+
+/// Turns a v2 status line into the outcome it describes.
+pub fn check_answer(line: &str) -> Result<(), SendError> {
+    #[derive(Deserialize)]
+    struct Answer {
+        status: String,
+        #[serde(default)]
+        cause: String,
+        #[serde(default)]
+        error: String,
+    }
+
+    let answer: Answer = serde_json::from_str(line)
+        .map_err(|_| SendError::Protocol(format!("unreadable answer {line:?}")))?;
+    match answer.status.as_str() {
+        "ok" => Ok(()),
+        _ => Err(SendError::Rejected {
+            cause: answer.cause,
+            error: answer.error,
+        }),
+    }
+}
+
+/// A public key as PEM or as the one-line base64 form vote sites take.
+pub fn parse_public_key(contents: &str) -> Result<RsaPublicKey, CryptoError> {
+    let contents = contents.trim();
+    if contents.starts_with("-----BEGIN") {
+        return RsaPublicKey::from_public_key_pem(contents).map_err(|e| CryptoError::InvalidPublicKey(e.to_string()));
+    }
+    RSAIO::parse_public_key_line(contents)
+}
+
+async fn read_line<S>(stream: &mut BufReader<S>) -> Result<String, SendError>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut line = String::new();
+    (&mut *stream).take(MAX_LINE).read_line(&mut line).await?;
+    if line.is_empty() {
+        return Err(SendError::Protocol("connection closed by the server".to_string()));
+    }
+    Ok(line.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyManager, RSAIO::KeyFormat};
+    use crate::file::tokens::Tokens;
+    use crate::file::TokenStore;
+    use crate::net::vote_handler::{VoteHandler, VoteHandlerError, VoteScope};
+    use rsa::pkcs8::{DecodePrivateKey, EncodePublicKey};
+    use rsa::RsaPrivateKey;
+    use voteme_api::{Timestamp, VoteProtocol};
+
+    const TEST_KEY: &str = include_str!("../../tests/fixtures/test_private.pem");
+
+    fn vote() -> Vote {
+        let mut vote = Vote::new(
+            "TestList",
+            "Alex",
+            "198.51.100.9",
+            Timestamp::from_millis(1_700_000_000_000),
+            VoteProtocol::V2,
+        );
+        vote.additional_data.insert("voteId".to_string(), "8f2c".into());
+        vote
+    }
+
+    /// Sends `vote()` to our own handler and returns what each side saw.
+    async fn round_trip(credentials: Credentials, token: &str) -> (Result<(), SendError>, Result<Vote, VoteHandlerError>) {
+        let key = RsaPrivateKey::from_pkcs8_pem(TEST_KEY).unwrap();
+        let keys = KeyManager::from_key(key, KeyFormat::Pem, Duration::from_secs(3600));
+        let tokens = TokenStore::from_tokens(
+            "tokens.toml",
+            Tokens {
+                fallback_to_default: false,
+                tokens: [("TestList".to_string(), token.to_string())].into(),
+            },
+        );
+        let (server, client) = tokio::io::duplex(4096);
+
+        let sender = tokio::spawn(async move {
+            send_over(client, &credentials, &vote())
+                .await
+                .and_then(|exchange| exchange.outcome())
+        });
+        let received = VoteHandler::handle(server, "challenge", &keys, &tokens, &VoteScope::default(), false).await;
+        (sender.await.unwrap(), received)
+    }
+
+    fn test_public_key() -> RsaPublicKey {
+        RsaPrivateKey::from_pkcs8_pem(TEST_KEY).unwrap().to_public_key()
+    }
+
+    #[tokio::test]
+    async fn v1_vote_reaches_the_handler() {
+        let (sent, received) = round_trip(Credentials::V1(test_public_key()), "testtoken").await;
+        assert!(sent.is_ok());
+        let received = received.unwrap();
+        assert_eq!(received.username, "Alex");
+        assert_eq!(received.timestamp, Timestamp::from_millis(1_700_000_000_000));
+        assert_eq!(received.protocol, VoteProtocol::V1);
+    }
+
+    #[tokio::test]
+    async fn v2_vote_is_acknowledged() {
+        let (sent, received) = round_trip(Credentials::V2("testtoken".to_string()), "testtoken").await;
+        assert!(sent.is_ok());
+        let received = received.unwrap();
+        assert_eq!(received.address, "198.51.100.9");
+        assert_eq!(received.additional_data, vote().additional_data);
+    }
+
+    #[tokio::test]
+    async fn v2_rejection_is_reported() {
+        let (sent, received) = round_trip(Credentials::V2("wrongtoken".to_string()), "testtoken").await;
+        assert!(matches!(received, Err(VoteHandlerError::InvalidSignature)));
+        assert!(matches!(sent, Err(SendError::Rejected { .. })));
+    }
+
+    #[test]
+    fn v2_packet_is_framed_and_signed() {
+        let mut vote = vote();
+        vote.additional_data.clear();
+        let packet = encode_v2(&vote, "testtoken", "challenge").unwrap();
+        assert_eq!(u16::from_be_bytes([packet[0], packet[1]]), V2_MAGIC);
+        assert_eq!(u16::from_be_bytes([packet[2], packet[3]]) as usize, packet.len() - 4);
+
+        let message: serde_json::Value = serde_json::from_slice(&packet[4..]).unwrap();
+        let payload = message["payload"].as_str().unwrap();
+        let fields: serde_json::Value = serde_json::from_str(payload).unwrap();
+        assert_eq!(fields["challenge"], "challenge");
+        assert_eq!(fields["timestamp"], 1700000000000i64);
+        assert!(fields.get("additionalData").is_none());
+        assert!(HMAC::verify(payload.as_bytes(), message["signature"].as_str().unwrap(), "testtoken"));
+    }
+
+    #[test]
+    fn answers_are_told_apart() {
+        assert!(check_answer(r#"{"status":"ok"}"#).is_ok());
+        assert!(matches!(
+            check_answer(r#"{"status":"error","cause":"CorruptedVoteException","error":"bad"}"#),
+            Err(SendError::Rejected { cause, .. }) if cause == "CorruptedVoteException"
+        ));
+        assert!(matches!(check_answer("hello"), Err(SendError::Protocol(_))));
+    }
+
+    #[test]
+    fn public_keys_are_read_as_pem_or_one_line() {
+        let key = test_public_key();
+        let line = RSAIO::public_key_line(&key).unwrap();
+        assert_eq!(parse_public_key(&line).unwrap(), key);
+
+        let pem = key.to_public_key_pem(Default::default()).unwrap();
+        assert_eq!(parse_public_key(&pem).unwrap(), key);
+        assert!(matches!(parse_public_key("not a key"), Err(CryptoError::InvalidPublicKey(_))));
+    }
+}
@@ -0,0 +1,15 @@
+//! Votifier receiver without Pumpkin: v1/v2 listeners, the HTTP webhook receiver,
+//! pollers, forwarding and the files under `plugins/VoteMe`. The VoteMe plugin
+//! and `voteme-daemon` are both thin wrappers around [`state::VoteMeState`] and
+//! [`net::listener::ListenerHandle`].
+
+pub mod crypto;
+pub mod doctor;
+pub mod file;
+pub mod forward;
+pub mod net;
+pub mod parser;
+pub mod poll;
+pub mod state;
+#[cfg(feature = "storage")]
+pub mod storage;
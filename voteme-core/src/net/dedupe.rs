@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use voteme_api::{Timestamp, Vote};
+
+/// Entries kept before expired ones are swept on insert.
+const SWEEP_AT: usize = 1024;
+
+/// Service, username and the vote time; no time for votes stamped on arrival.
+type Key = (String, String, Option<Timestamp>);
+
+/// Remembers recently delivered votes so a sender retrying the same vote, or
+/// one arriving over both TCP and HTTP, is only emitted once.
+#[derive(Default)]
+pub struct DedupeCache {
+    seen: Mutex<HashMap<Key, Instant>>,
+}
+
+impl DedupeCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `vote` and returns `false` if the same service, username and
+    /// timestamp were already seen within `window`. A zero window disables it.
+    ///
+    /// Votes whose list sends no timestamp ([`Vote::stamped_on_arrival`], e.g.
+    /// webhook adapters) match on service and username alone, so a retried
+    /// webhook isn't taken for a new vote.
+    pub fn first_seen(&self, vote: &Vote, window: Duration, now: Instant) -> bool {
+        if window.is_zero() {
+            return true;
+        }
+
+        let mut seen = self.seen.lock().unwrap();
+        if seen.len() >= SWEEP_AT {
+            seen.retain(|_, at| now.saturating_duration_since(*at) < window);
+        }
+
+        let at = (!vote.stamped_on_arrival()).then_some(vote.timestamp);
+        let key = (vote.service_name.clone(), vote.username.clone(), at);
+        match seen.get(&key) {
+            Some(at) if now.saturating_duration_since(*at) < window => false,
+            _ => {
+                seen.insert(key, now);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use voteme_api::VoteProtocol;
+
+    const WINDOW: Duration = Duration::from_secs(600);
+
+    #[test]
+    fn same_timestamp_is_a_duplicate() {
+        let cache = DedupeCache::new();
+        let now = Instant::now();
+        let vote = |at| Vote::new("TestList", "Alex", "", Timestamp::from_secs(at), VoteProtocol::V2);
+
+        assert!(cache.first_seen(&vote(1_700_000_000), WINDOW, now));
+        assert!(!cache.first_seen(&vote(1_700_000_000), WINDOW, now));
+        assert!(cache.first_seen(&vote(1_700_000_001), WINDOW, now));
+        assert!(cache.first_seen(&vote(1_700_000_000), WINDOW, now + WINDOW));
+    }
+
+    #[test]
+    fn retried_webhooks_without_a_timestamp_are_duplicates() {
+        let cache = DedupeCache::new();
+        let now = Instant::now();
+        let first = Vote::received("top.gg", "395526710101278721", "", VoteProtocol::Http);
+        let retry = Vote {
+            timestamp: Timestamp::from_millis(first.timestamp.as_millis() + 5_000),
+            received_at: Timestamp::from_millis(first.received_at.as_millis() + 5_000),
+            ..first.clone()
+        };
+
+        assert!(cache.first_seen(&first, WINDOW, now));
+        assert!(!cache.first_seen(&retry, WINDOW, now + Duration::from_secs(5)));
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+
+/// Prefix marking a listener address as a Unix domain socket path.
+pub const UNIX_PREFIX: &str = "unix:";
+
+/// Any accepted connection, TCP or Unix socket.
+pub trait VoteStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> VoteStream for T {}
+
+/// Who is on the other end of a vote connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Peer {
+    Tcp(SocketAddr),
+    /// Local process on the Unix socket at this path.
+    Unix(String),
+}
+
+impl Peer {
+    pub fn ip(&self) -> Option<IpAddr> {
+        match self {
+            Peer::Tcp(addr) => Some(addr.ip()),
+            Peer::Unix(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Peer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Peer::Tcp(addr) => write!(f, "{addr}"),
+            Peer::Unix(path) => write!(f, "{UNIX_PREFIX}{path}"),
+        }
+    }
+}
+
+/// A bound listener socket. The socket file of a Unix listener is removed on drop.
+pub enum Endpoint {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, String),
+}
+
+impl Endpoint {
+    /// Binds `host:port`, `[v6]:port` or `unix:/path/to.sock`.
+    pub async fn bind(address: &str) -> io::Result<Self> {
+        let Some(path) = address.strip_prefix(UNIX_PREFIX) else {
+            return TcpListener::bind(address).await.map(Endpoint::Tcp);
+        };
+
+        #[cfg(unix)]
+        {
+            // A socket file left behind by an unclean exit would make the bind fail.
+            if std::fs::symlink_metadata(path).is_ok_and(|meta| {
+                use std::os::unix::fs::FileTypeExt;
+                meta.file_type().is_socket()
+            }) {
+                std::fs::remove_file(path)?;
+            }
+            if let Some(parent) = std::path::Path::new(path).parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            tokio::net::UnixListener::bind(path).map(|l| Endpoint::Unix(l, path.to_string()))
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = path;
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Unix socket listeners are not supported on this platform",
+            ))
+        }
+    }
+
+    pub async fn accept(&self) -> io::Result<(Box<dyn VoteStream>, Peer)> {
+        match self {
+            Endpoint::Tcp(listener) => {
+                let (socket, addr) = listener.accept().await?;
+                // IPv4 clients of a dual-stack socket show up as ::ffff:a.b.c.d.
+                let addr = SocketAddr::new(addr.ip().to_canonical(), addr.port());
+                Ok((Box::new(socket), Peer::Tcp(addr)))
+            }
+            #[cfg(unix)]
+            Endpoint::Unix(listener, path) => {
+                let (socket, _) = listener.accept().await?;
+                Ok((Box::new(socket), Peer::Unix(path.clone())))
+            }
+        }
+    }
+}
+
+impl Drop for Endpoint {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Endpoint::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
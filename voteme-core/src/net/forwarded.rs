@@ -0,0 +1,105 @@
+use std::net::{IpAddr, SocketAddr};
+
+use hyper::header::{HeaderMap, FORWARDED};
+
+use crate::file::Config;
+
+/// De facto header most reverse proxies append the client address to.
+pub const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Client address of an HTTP request that came through `trusted_proxies`: the
+/// right-most hop of `Forwarded` (or else `X-Forwarded-For`) that isn't a trusted
+/// proxy itself. Hops further left were written by the client and can be forged.
+///
+/// `None` when neither header is present or that hop isn't an IP address
+/// (`unknown`, an obfuscated identifier), so the peer address stays as is.
+pub fn client_ip(headers: &HeaderMap, config: &Config) -> Option<IpAddr> {
+    let hops = match joined(headers, FORWARDED.as_str()) {
+        Some(forwarded) => forwarded.split(',').map(forwarded_for).collect::<Vec<_>>(),
+        None => joined(headers, X_FORWARDED_FOR)?
+            .split(',')
+            .map(|hop| parse_hop(hop.trim()))
+            .collect(),
+    };
+
+    for hop in hops.iter().rev() {
+        let ip = (*hop)?.to_canonical();
+        if !config.trusts_proxy(ip) {
+            return Some(ip);
+        }
+    }
+    hops.first().copied().flatten()
+}
+
+/// All values of `name`, as one comma-separated list.
+fn joined(headers: &HeaderMap, name: &str) -> Option<String> {
+    let values: Vec<&str> = headers.get_all(name).iter().filter_map(|v| v.to_str().ok()).collect();
+    (!values.is_empty()).then(|| values.join(","))
+}
+
+/// The `for=` parameter of one `Forwarded` element, e.g. `for="[2001:db8::1]:4711";proto=https`.
+fn forwarded_for(element: &str) -> Option<IpAddr> {
+    element.split(';').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        key.trim().eq_ignore_ascii_case("for").then(|| parse_hop(value.trim().trim_matches('"')))?
+    })
+}
+
+/// `192.0.2.60`, `192.0.2.60:4711`, `2001:db8::1` or `[2001:db8::1]:4711`.
+fn parse_hop(hop: &str) -> Option<IpAddr> {
+    hop.parse::<IpAddr>()
+        .ok()
+        .or_else(|| hop.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .or_else(|| hop.strip_prefix('[')?.strip_suffix(']')?.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    fn proxy_config() -> Config {
+        Config {
+            trusted_proxies: vec!["10.0.0.0/8".parse().unwrap()],
+            ..Config::default()
+        }
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn right_most_untrusted_hop_is_the_client() {
+        let config = proxy_config();
+        let forged = headers(&[(X_FORWARDED_FOR, "192.0.2.1, 198.51.100.22, 10.0.0.7")]);
+        assert_eq!(client_ip(&forged, &config), Some("198.51.100.22".parse().unwrap()));
+
+        let split = headers(&[(X_FORWARDED_FOR, "198.51.100.22"), (X_FORWARDED_FOR, "10.0.0.7")]);
+        assert_eq!(client_ip(&split, &config), Some("198.51.100.22".parse().unwrap()));
+    }
+
+    #[test]
+    fn forwarded_header_wins_and_accepts_ports_and_brackets() {
+        let config = proxy_config();
+        let both = headers(&[
+            ("forwarded", r#"for="[2001:db8::1]:4711";proto=https, for=10.0.0.7"#),
+            (X_FORWARDED_FOR, "198.51.100.22"),
+        ]);
+        assert_eq!(client_ip(&both, &config), Some("2001:db8::1".parse().unwrap()));
+
+        let with_port = headers(&[("forwarded", "for=198.51.100.22:35646")]);
+        assert_eq!(client_ip(&with_port, &config), Some("198.51.100.22".parse().unwrap()));
+    }
+
+    #[test]
+    fn missing_or_unknown_hops_keep_the_peer() {
+        let config = proxy_config();
+        assert_eq!(client_ip(&HeaderMap::new(), &config), None);
+        assert_eq!(client_ip(&headers(&[("forwarded", "for=unknown")]), &config), None);
+    }
+}
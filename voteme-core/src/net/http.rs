@@ -0,0 +1,326 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{HeaderMap, CONTENT_TYPE};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use voteme_api::Vote;
+
+use crate::file::config::HttpConfig;
+use crate::file::TokenStore;
+use crate::net::endpoint::Peer;
+use crate::net::forwarded;
+use crate::net::intake::{self, ActiveConnection, Intake};
+use crate::net::vote_handler::VoteHandlerError;
+use crate::parser::adapters::voteme::authenticate;
+use crate::parser::http_adapter::{self, HttpRequest};
+use crate::parser::vote_parser::VoteParser;
+use crate::state::{ListenerStatus, VoteMeState, VoteStats};
+
+/// HMAC-SHA256 of the request body keyed with the service token.
+pub const SIGNATURE_HEADER: &str = "x-voteme-signature";
+/// The service token itself, for senders that can't sign.
+pub const TOKEN_HEADER: &str = "x-voteme-token";
+
+/// Optional webhook endpoint for vote lists that POST votes instead of speaking
+/// Votifier. `http.path` takes VoteMe's own format and each `[[http.hooks]]`
+/// path its adapter's. Votes go through the same [`Intake`] checks as TCP votes.
+pub struct HttpReceiver;
+
+impl HttpReceiver {
+    pub async fn run(state: Arc<VoteMeState>, mut shutdown: watch::Receiver<bool>) {
+        let mut addr_rx = state.subscribe_http();
+        let mut address = addr_rx.borrow_and_update().clone();
+        let mut listener = Self::bind(&state, address.as_deref()).await;
+        let mut connections = JoinSet::new();
+
+        loop {
+            tokio::select! {
+                _ = shutdown.changed() => break,
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+                changed = addr_rx.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    drop(listener.take());
+                    if let Some(old) = &address {
+                        state.remove_listener(&label(old));
+                    }
+                    address = addr_rx.borrow_and_update().clone();
+                    listener = Self::bind(&state, address.as_deref()).await;
+                }
+                accepted = Self::accept(listener.as_ref()) => match accepted {
+                    Ok((stream, addr)) => {
+                        VoteStats::inc(&state.stats.connections);
+                        let addr = SocketAddr::new(addr.ip().to_canonical(), addr.port());
+                        if !Intake::has_capacity(&state, &Peer::Tcp(addr)) {
+                            continue;
+                        }
+                        // Behind a trusted proxy the client is only known once the headers are read.
+                        let config = state.config();
+                        if !config.trusts_proxy(addr.ip()) && !Intake::admit(&state, &config, addr) {
+                            continue;
+                        }
+                        let active = ActiveConnection::new(state.clone());
+                        connections.spawn(Self::serve(active, stream, addr));
+                    }
+                    Err(e) => {
+                        log::error!("HTTP accept error: {}", e);
+                    }
+                },
+            }
+        }
+
+        drop(listener);
+        if let Some(address) = &address {
+            state.set_listener(&label(address), ListenerStatus::Stopped);
+        }
+
+        let drain = Duration::from_secs(state.config.read().unwrap().shutdown_timeout_secs);
+        intake::drain(&mut connections, drain).await;
+    }
+
+    async fn bind(state: &VoteMeState, address: Option<&str>) -> Option<TcpListener> {
+        let address = address?;
+        match TcpListener::bind(address).await {
+            Ok(l) => {
+                log::info!("Listening for HTTP votes on {}", address);
+                state.set_listener(&label(address), ListenerStatus::Listening);
+                Some(l)
+            }
+            Err(e) => {
+                log::error!("HTTP bind failed for {}: {}", address, e);
+                state.set_listener(&label(address), ListenerStatus::Failed(e.to_string()));
+                None
+            }
+        }
+    }
+
+    async fn accept(listener: Option<&TcpListener>) -> std::io::Result<(TcpStream, SocketAddr)> {
+        match listener {
+            Some(l) => l.accept().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// One request per connection; the whole exchange must fit in `read_timeout_secs`.
+    async fn serve(active: ActiveConnection, stream: TcpStream, addr: SocketAddr) {
+        let state = active.0.clone();
+        let read_timeout = Duration::from_secs(state.config.read().unwrap().read_timeout_secs);
+
+        let service = service_fn(move |req| {
+            let state = state.clone();
+            async move { Ok::<_, Infallible>(Self::respond(&state, addr, req).await) }
+        });
+        let connection = http1::Builder::new()
+            .keep_alive(false)
+            .serve_connection(TokioIo::new(stream), service);
+
+        match tokio::time::timeout(read_timeout, connection).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => log::debug!("HTTP connection from {} failed: {}", addr, e),
+            Err(_) => {
+                VoteStats::inc(&active.0.stats.timeouts);
+                log::warn!("Vote error {}: {}", addr, VoteHandlerError::Timeout);
+            }
+        }
+    }
+
+    async fn respond(state: &VoteMeState, addr: SocketAddr, req: Request<Incoming>) -> Response<Full<Bytes>> {
+        VoteStats::inc(&state.stats.http_requests);
+        let config = state.config();
+
+        let addr = if config.trusts_proxy(addr.ip()) {
+            let client = forwarded::client_ip(req.headers(), &config).map_or(addr, |ip| SocketAddr::new(ip, 0));
+            if config.debug {
+                log::debug!("Proxy {} forwarded HTTP request from {}", addr, client.ip());
+            }
+            if !Intake::admit(state, &config, client) {
+                return reply(StatusCode::FORBIDDEN, "refused", "client refused");
+            }
+            client
+        } else {
+            addr
+        };
+
+        let hook = config.http.hook(req.uri().path());
+        let adapter = match hook {
+            Some(hook) => match http_adapter::adapter(&hook.adapter) {
+                Some(adapter) => Some(adapter),
+                None => {
+                    log::error!("Unknown HTTP adapter {:?} for {}", hook.adapter, hook.path);
+                    return reply(StatusCode::INTERNAL_SERVER_ERROR, "error", "misconfigured hook");
+                }
+            },
+            None if req.uri().path() != config.http.path => {
+                return reply(StatusCode::NOT_FOUND, "error", "not found");
+            }
+            None => None,
+        };
+
+        let allowed = adapter.map_or(req.method() == Method::POST, |a| a.accepts(req.method()));
+        if !allowed {
+            return reply(StatusCode::METHOD_NOT_ALLOWED, "error", "method not allowed");
+        }
+
+        let (parts, body) = req.into_parts();
+        let body = match Limited::new(body, config.http.max_body_bytes).collect().await {
+            Ok(body) => body.to_bytes(),
+            Err(_) => return reply(StatusCode::PAYLOAD_TOO_LARGE, "error", "body too large"),
+        };
+
+        let result = match hook.zip(adapter) {
+            Some((hook, adapter)) => {
+                let request = HttpRequest {
+                    method: &parts.method,
+                    uri: &parts.uri,
+                    headers: &parts.headers,
+                    body: &body,
+                };
+                state
+                    .tokens
+                    .get(&hook.service)
+                    .ok_or_else(|| VoteHandlerError::UnknownService(hook.service.clone()))
+                    .and_then(|token| adapter.parse(&request, hook, &token))
+            }
+            None => read_vote(&state.tokens, &config.http, &parts.headers, &body),
+        };
+
+        match Intake::deliver(state, &config, &Peer::Tcp(addr), result) {
+            Ok(()) => reply(StatusCode::OK, "ok", ""),
+            Err(e @ VoteHandlerError::Duplicate(_)) => reply(StatusCode::OK, "duplicate", &e.to_string()),
+            Err(e) => reply(status_for(&e), e.cause(), &e.to_string()),
+        }
+    }
+}
+
+/// Decodes and authenticates a webhook vote against the service's token.
+pub fn read_vote(
+    tokens: &TokenStore,
+    http: &HttpConfig,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<Vote, VoteHandlerError> {
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let vote = VoteParser::parse_http(content_type, body)?;
+    if vote.service_name.is_empty() {
+        return Err(VoteHandlerError::InvalidPacket("Missing serviceName".to_string()));
+    }
+
+    let token = tokens
+        .get(&vote.service_name)
+        .ok_or_else(|| VoteHandlerError::UnknownService(vote.service_name.clone()))?;
+    authenticate(headers, body, http.auth_for(&vote.service_name), &token)?;
+
+    Ok(vote)
+}
+
+fn status_for(e: &VoteHandlerError) -> StatusCode {
+    match e {
+        VoteHandlerError::InvalidPacket(_) | VoteHandlerError::InvalidUtf8(_) => StatusCode::BAD_REQUEST,
+        VoteHandlerError::UnknownService(_) | VoteHandlerError::InvalidSignature => StatusCode::UNAUTHORIZED,
+        VoteHandlerError::Filtered(_) => StatusCode::FORBIDDEN,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// JSON answer shaped like a v2 status line.
+fn reply(status: StatusCode, outcome: &str, error: &str) -> Response<Full<Bytes>> {
+    let body = if error.is_empty() {
+        serde_json::json!({ "status": outcome })
+    } else if status.is_success() {
+        serde_json::json!({ "status": outcome, "error": error })
+    } else {
+        serde_json::json!({ "status": "error", "cause": outcome, "error": error })
+    };
+
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Full::new(Bytes::from(body.to_string())))
+        .expect("static response parts are valid")
+}
+
+fn label(address: &str) -> String {
+    format!("http://{address}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::HMAC;
+    use crate::file::config::HttpAuth;
+    use crate::file::tokens::Tokens;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use hyper::header::HeaderValue;
+    use voteme_api::{Timestamp, VoteProtocol};
+
+    const TOKEN: &str = "testtoken";
+
+    fn test_tokens() -> TokenStore {
+        let tokens = Tokens {
+            fallback_to_default: false,
+            tokens: [("TestList".to_string(), TOKEN.to_string())].into(),
+        };
+        TokenStore::from_tokens("tokens.toml", tokens)
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn json_vote_with_hmac_header() {
+        let body = br#"{"serviceName":"TestList","username":"Alex","address":"198.51.100.9","timestamp":1700000000000}"#;
+        let signature = STANDARD.encode(HMAC::sign(body, TOKEN));
+        let http = HttpConfig::default();
+
+        let signed = headers(&[("content-type", "application/json"), (SIGNATURE_HEADER, &signature)]);
+        let vote = read_vote(&test_tokens(), &http, &signed, body).unwrap();
+        assert_eq!(vote.username, "Alex");
+        assert_eq!(vote.timestamp, Timestamp::from_millis(1_700_000_000_000));
+        assert_eq!(vote.protocol, VoteProtocol::Http);
+
+        let unsigned = headers(&[("content-type", "application/json")]);
+        assert!(matches!(
+            read_vote(&test_tokens(), &http, &unsigned, body),
+            Err(VoteHandlerError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn form_vote_with_shared_secret() {
+        let body = b"serviceName=TestList&username=Steve&timestamp=1700000000";
+        let http = HttpConfig {
+            service_auth: [("TestList".to_string(), HttpAuth::Secret)].into(),
+            ..HttpConfig::default()
+        };
+        let form = "application/x-www-form-urlencoded";
+
+        let vote = read_vote(&test_tokens(), &http, &headers(&[("content-type", form), (TOKEN_HEADER, TOKEN)]), body).unwrap();
+        assert_eq!(vote.username, "Steve");
+        assert_eq!(vote.address, "");
+        assert_eq!(vote.timestamp, Timestamp::from_secs(1_700_000_000));
+
+        assert!(matches!(
+            read_vote(&test_tokens(), &http, &headers(&[("content-type", form), (TOKEN_HEADER, "wrong")]), body),
+            Err(VoteHandlerError::InvalidSignature)
+        ));
+    }
+}
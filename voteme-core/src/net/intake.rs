@@ -0,0 +1,183 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::task::JoinSet;
+use voteme_api::Vote;
+
+use crate::file::Config;
+use crate::net::endpoint::Peer;
+use crate::net::rate_limit::{Admission, RateLimits};
+use crate::net::vote_handler::VoteHandlerError;
+use crate::state::{VoteMeState, VoteStats};
+
+/// Stops counting a connection as active when its task ends, aborted or not.
+pub struct ActiveConnection(pub Arc<VoteMeState>);
+
+impl ActiveConnection {
+    pub fn new(state: Arc<VoteMeState>) -> Self {
+        VoteStats::inc(&state.stats.active);
+        ActiveConnection(state)
+    }
+}
+
+impl Drop for ActiveConnection {
+    fn drop(&mut self) {
+        VoteStats::dec(&self.0.stats.active);
+    }
+}
+
+/// Waits up to `timeout` for in-flight connections, then aborts the rest.
+pub async fn drain(connections: &mut JoinSet<()>, timeout: Duration) {
+    let in_flight = connections.len();
+    if in_flight == 0 {
+        return;
+    }
+
+    log::info!("Waiting up to {}s for {} in-flight votes", timeout.as_secs(), in_flight);
+    let drained = tokio::time::timeout(timeout, async {
+        while connections.join_next().await.is_some() {}
+    })
+    .await;
+    if drained.is_err() {
+        log::warn!("Aborting {} votes still in flight", connections.len());
+        connections.abort_all();
+    }
+}
+
+/// Checks shared by every way a vote can arrive (Votifier TCP, HTTP webhooks):
+/// connection admission before anything is read, and delivery once a vote is parsed.
+pub struct Intake;
+
+impl Intake {
+    /// Applies the global connection cap; over the cap the socket is dropped right away.
+    pub fn has_capacity(state: &VoteMeState, peer: &Peer) -> bool {
+        let config = state.config.read().unwrap();
+        if VoteStats::get(&state.stats.active) < config.max_connections {
+            return true;
+        }
+
+        VoteStats::inc(&state.stats.over_capacity);
+        if config.debug {
+            log::debug!("Refused {}: {} connections already active", peer, config.max_connections);
+        }
+        false
+    }
+
+    /// Applies the IP lists and the per-IP rate limit to the client address.
+    /// Refused sockets are dropped before anything is sent.
+    pub fn admit(state: &VoteMeState, config: &Config, addr: SocketAddr) -> bool {
+        if let Err(reason) = config.ip_filter.check_peer(addr.ip()) {
+            VoteStats::inc(&state.stats.filtered);
+            log::warn!("Refused connection from {}: {}", addr, reason);
+            return false;
+        }
+
+        let limits = RateLimits::from_config(config);
+        match state.limiter.check(addr.ip(), &limits, Instant::now()) {
+            Admission::Allowed => true,
+            Admission::RateLimited => {
+                VoteStats::inc(&state.stats.rate_limited);
+                if config.debug {
+                    log::debug!("Refused {}: rate limited", addr);
+                }
+                false
+            }
+            Admission::Banned(left) => {
+                VoteStats::inc(&state.stats.rate_limited);
+                if config.debug {
+                    log::debug!("Refused {}: banned for another {}s", addr, left.as_secs());
+                }
+                false
+            }
+        }
+    }
+
+    /// Takes the outcome of reading a vote from `peer`: applies the per-service IP
+    /// lists and duplicate check, updates stats, the rate limiter and the log, and
+    /// emits accepted votes. Returns the final outcome for the caller to answer with.
+    pub fn deliver(
+        state: &VoteMeState,
+        config: &Config,
+        peer: &Peer,
+        result: Result<Vote, VoteHandlerError>,
+    ) -> Result<(), VoteHandlerError> {
+        let ip = peer.ip();
+
+        let result = result.and_then(|vote| match ip.map(|ip| config.ip_filter.check_service(&vote.service_name, ip)) {
+            Some(Err(reason)) => Err(VoteHandlerError::Filtered(reason)),
+            _ => Ok(vote),
+        });
+
+        let window = Duration::from_secs(config.dedupe_window_secs);
+        let result = result.and_then(|vote| {
+            if state.dedupe.first_seen(&vote, window, Instant::now()) {
+                Ok(vote)
+            } else {
+                Err(VoteHandlerError::Duplicate(vote.username))
+            }
+        });
+
+        if let Some(ip) = ip {
+            let limits = RateLimits::from_config(config);
+            match &result {
+                Ok(_) => state.limiter.forgive(ip),
+                // A dropped connection or a retried vote isn't the sender's fault.
+                Err(VoteHandlerError::Io(_) | VoteHandlerError::Duplicate(_)) => {}
+                Err(_) => {
+                    if state.limiter.strike(ip, &limits, Instant::now()) {
+                        log::warn!(
+                            "Banned {} for {} minutes after {} invalid votes",
+                            ip,
+                            limits.ban_duration.as_secs() / 60,
+                            limits.ban_after
+                        );
+                    }
+                }
+            }
+        }
+
+        let e = match result {
+            Ok(mut vote) => {
+                vote.probe = state.probe.expects(&vote);
+                VoteStats::inc(&state.stats.accepted);
+                log::info!(
+                    "Received vote from {} for service {}",
+                    vote.username, vote.service_name
+                );
+
+                state.vote_service.emit(vote);
+                return Ok(());
+            }
+            Err(e) => e,
+        };
+
+        match &e {
+            VoteHandlerError::Rejected
+            | VoteHandlerError::Crypto(_)
+            | VoteHandlerError::InvalidSignature
+            | VoteHandlerError::ChallengeMismatch
+            | VoteHandlerError::UnknownService(_) => {
+                VoteStats::inc(&state.stats.rejected);
+                log::warn!("Rejected vote from {}: {}", peer, e);
+            }
+            VoteHandlerError::Filtered(_) => {
+                VoteStats::inc(&state.stats.filtered);
+                log::warn!("Rejected vote from {}: {}", peer, e);
+            }
+            VoteHandlerError::Duplicate(_) => {
+                VoteStats::inc(&state.stats.duplicates);
+                log::info!("Ignored vote from {}: {}", peer, e);
+            }
+            VoteHandlerError::Timeout => {
+                VoteStats::inc(&state.stats.timeouts);
+                log::warn!("Vote error {}: {}", peer, e);
+            }
+            _ => {
+                VoteStats::inc(&state.stats.errors);
+                log::warn!("Vote error {}: {}", peer, e);
+            }
+        }
+        Err(e)
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use ipnet::IpNet;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A CIDR range such as `203.0.113.0/24` or `2001:db8::/32`. A bare address
+/// is read as a single-host range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr(pub IpNet);
+
+impl Cidr {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        self.0.contains(&ip)
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(net) = s.parse::<IpNet>() {
+            return Ok(Cidr(net.trunc()));
+        }
+        s.parse::<IpAddr>()
+            .map(|ip| Cidr(IpNet::from(ip)))
+            .map_err(|_| format!("Invalid IP or CIDR range: {s}"))
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Serialize for Cidr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Cidr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// An allow and a deny list. Deny wins; an empty allow list allows everyone.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IpLists {
+    #[serde(default)]
+    pub allow: Vec<Cidr>,
+    #[serde(default)]
+    pub deny: Vec<Cidr>,
+}
+
+impl IpLists {
+    /// `Err` carries the reason the address was refused.
+    pub fn check(&self, ip: IpAddr) -> Result<(), String> {
+        if let Some(range) = self.deny.iter().find(|r| r.contains(ip)) {
+            return Err(format!("{ip} is in deny range {range}"));
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|r| r.contains(ip)) {
+            return Err(format!("{ip} is not in the allow list"));
+        }
+        Ok(())
+    }
+}
+
+/// `[ip_filter]` in `Config.toml`. The top-level lists are checked when a
+/// connection is accepted; `[ip_filter.services.<name>]` lists are checked
+/// once the vote is parsed and its service name is known.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IpFilter {
+    #[serde(flatten)]
+    pub global: IpLists,
+    #[serde(default)]
+    pub services: BTreeMap<String, IpLists>,
+}
+
+impl IpFilter {
+    pub fn check_peer(&self, ip: IpAddr) -> Result<(), String> {
+        self.global.check(ip.to_canonical())
+    }
+
+    pub fn check_service(&self, service: &str, ip: IpAddr) -> Result<(), String> {
+        match self.services.get(service) {
+            Some(lists) => lists
+                .check(ip.to_canonical())
+                .map_err(|reason| format!("{reason} for service {service}")),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_ranges_and_bare_addresses() {
+        let filter: IpFilter = toml::from_str(
+            r#"
+            allow = ["203.0.113.0/24", "2001:db8::/32", "198.51.100.7"]
+            deny = ["203.0.113.66"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(filter.check_peer(ip("203.0.113.10")).is_ok());
+        assert!(filter.check_peer(ip("198.51.100.7")).is_ok());
+        assert!(filter.check_peer(ip("2001:db8::1")).is_ok());
+        // IPv4 seen through a dual-stack socket.
+        assert!(filter.check_peer(ip("::ffff:203.0.113.10")).is_ok());
+
+        assert!(filter.check_peer(ip("203.0.113.66")).is_err());
+        assert!(filter.check_peer(ip("198.51.100.8")).is_err());
+        assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+    }
+
+    #[test]
+    fn service_lists_apply_only_to_their_service() {
+        let filter: IpFilter = toml::from_str(
+            r#"
+            [services.TopG]
+            allow = ["192.0.2.0/24"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(filter.check_peer(ip("198.51.100.1")).is_ok());
+        assert!(filter.check_service("TopG", ip("192.0.2.5")).is_ok());
+        assert!(filter.check_service("TopG", ip("198.51.100.1")).is_err());
+        assert!(filter.check_service("Other", ip("198.51.100.1")).is_ok());
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::io::AsyncRead;
+use tokio::runtime::Runtime;
+use tokio::sync::{mpsc, watch};
+use tokio::task::{JoinHandle, JoinSet};
+
+use crate::crypto::HMAC;
+use crate::file::config::ListenerConfig;
+use crate::file::{watcher, Config};
+use crate::forward::Forwarder;
+use crate::net::endpoint::{Endpoint, Peer, VoteStream};
+use crate::net::http::HttpReceiver;
+use crate::net::proxy_protocol;
+use crate::net::intake::{self, ActiveConnection, Intake};
+use crate::net::rate_limit::RateLimits;
+use crate::net::vote_handler::{VoteHandler, VoteHandlerError};
+use crate::poll::{PlayerSource, Poller, SeenStore};
+use crate::state::{ListenerStatus, VoteMeState, VoteStats};
+
+/// Owned listeners: a dedicated runtime running the Votifier and HTTP accept
+/// loops, the pollers and the forwarder, plus the signal that stops them.
+/// Dropping it without [`ListenerHandle::shutdown`] stops the runtime at once.
+pub struct ListenerHandle {
+    /// Only `None` once `shutdown` or `drop` took it.
+    runtime: Option<Runtime>,
+    shutdown: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl ListenerHandle {
+    pub fn start(state: Arc<VoteMeState>, players: Arc<dyn PlayerSource>) -> Result<Self, String> {
+        let seen = Arc::new(SeenStore::new_default());
+        if let Err(e) = seen.load() {
+            log::error!("{e}; pollers start without their reported votes");
+        }
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("voteme-listener")
+            .build()
+            .map_err(|e| format!("Failed to create Tokio runtime: {e}"))?;
+
+        let votes = Forwarder::subscribe(&state.vote_service);
+        let (shutdown, shutdown_rx) = watch::channel(false);
+        runtime.spawn(watcher::watch_files(state.clone()));
+        let tasks = vec![
+            runtime.spawn(VoteListener::run(state.clone(), shutdown_rx.clone())),
+            runtime.spawn(HttpReceiver::run(state.clone(), shutdown_rx.clone())),
+            runtime.spawn(Poller::run(state.clone(), seen, players, shutdown_rx.clone())),
+            runtime.spawn(Forwarder::run(state, votes, shutdown_rx)),
+        ];
+
+        Ok(ListenerHandle {
+            runtime: Some(runtime),
+            shutdown,
+            tasks,
+        })
+    }
+
+    /// Runs `task` on the listener runtime; it's dropped at shutdown.
+    pub fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        if let Some(runtime) = &self.runtime {
+            runtime.spawn(task);
+        }
+    }
+
+    /// Stops accepting, waits up to `drain` for in-flight votes, then tears the runtime down.
+    pub async fn shutdown(mut self, drain: Duration) {
+        let _ = self.shutdown.send(true);
+        let Some(runtime) = self.runtime.take() else {
+            return;
+        };
+
+        // Waiting happens on the listener runtime, which owns the timer.
+        let tasks = std::mem::take(&mut self.tasks);
+        let waiter = runtime.spawn(async move {
+            // A little slack on top of the drain the loops themselves enforce.
+            let _ = tokio::time::timeout(drain + Duration::from_secs(1), async {
+                for task in tasks {
+                    let _ = task.await;
+                }
+            })
+            .await;
+        });
+        let _ = waiter.await;
+
+        runtime.shutdown_background();
+        log::info!("Vote listener stopped.");
+    }
+}
+
+/// Dropping a `Runtime` from async code panics, so a handle that was never shut
+/// down (a load error after `start`, an unload that never ran) stops it in the background.
+impl Drop for ListenerHandle {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            let _ = self.shutdown.send(true);
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// How often idle rate limit buckets are dropped.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A connection handed from an accept task to the main loop, tagged with its listener address.
+type Accepted = (Box<dyn VoteStream>, Peer, String);
+
+/// Accept loops for every configured listener. Listeners whose address changes
+/// are rebound; connections already accepted keep running on their own settings.
+pub struct VoteListener;
+
+impl VoteListener {
+    pub async fn run(state: Arc<VoteMeState>, mut shutdown: watch::Receiver<bool>) {
+        let mut configs_rx = state.subscribe_listeners();
+        let (accepted_tx, mut accepted_rx) = mpsc::channel::<Accepted>(64);
+        let mut acceptors: HashMap<String, JoinHandle<()>> = HashMap::new();
+        let mut scopes: HashMap<String, Arc<ListenerConfig>> = HashMap::new();
+        let mut connections = JoinSet::new();
+        let mut prune = tokio::time::interval(PRUNE_INTERVAL);
+
+        let configs = configs_rx.borrow_and_update().clone();
+        Self::apply(&state, configs, &mut acceptors, &mut scopes, &accepted_tx).await;
+
+        loop {
+            tokio::select! {
+                _ = shutdown.changed() => break,
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+                _ = prune.tick() => {
+                    let limits = RateLimits::from_config(&state.config.read().unwrap());
+                    state.limiter.prune(&limits, Instant::now());
+                }
+                changed = configs_rx.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    let configs = configs_rx.borrow_and_update().clone();
+                    Self::apply(&state, configs, &mut acceptors, &mut scopes, &accepted_tx).await;
+                }
+                Some((socket, peer, address)) = accepted_rx.recv() => {
+                    VoteStats::inc(&state.stats.connections);
+                    let Some(listener) = scopes.get(&address).cloned() else {
+                        continue;
+                    };
+                    if !Intake::has_capacity(&state, &peer) {
+                        continue;
+                    }
+                    let active = ActiveConnection::new(state.clone());
+                    connections.spawn(Self::handle_connection(active, socket, peer, listener));
+                }
+            }
+        }
+
+        // Release the ports before draining so a restarted listener can bind them.
+        for (address, acceptor) in acceptors.drain() {
+            Self::stop(acceptor).await;
+            state.set_listener(&address, ListenerStatus::Stopped);
+        }
+
+        let drain = Duration::from_secs(state.config.read().unwrap().shutdown_timeout_secs);
+        intake::drain(&mut connections, drain).await;
+    }
+
+    /// Brings the running listeners in line with `configs`. Removed addresses are
+    /// released before new ones are bound, since they may share a port.
+    async fn apply(
+        state: &VoteMeState,
+        configs: Vec<ListenerConfig>,
+        acceptors: &mut HashMap<String, JoinHandle<()>>,
+        scopes: &mut HashMap<String, Arc<ListenerConfig>>,
+        accepted_tx: &mpsc::Sender<Accepted>,
+    ) {
+        let removed: Vec<String> = acceptors
+            .keys()
+            .filter(|address| !configs.iter().any(|c| &c.address == *address))
+            .cloned()
+            .collect();
+        for address in removed {
+            if let Some(acceptor) = acceptors.remove(&address) {
+                Self::stop(acceptor).await;
+            }
+            log::info!("Stopped vote listener on {}", address);
+            state.remove_listener(&address);
+        }
+
+        // Scope changes apply to new connections without a rebind.
+        scopes.clear();
+        for config in configs {
+            let address = config.address.clone();
+            scopes.insert(address.clone(), Arc::new(config));
+            if acceptors.contains_key(&address) {
+                continue;
+            }
+
+            match Endpoint::bind(&address).await {
+                Ok(endpoint) => {
+                    log::info!("Listening for votes on {}", address);
+                    state.set_listener(&address, ListenerStatus::Listening);
+                    let task = tokio::spawn(Self::accept_loop(endpoint, address.clone(), accepted_tx.clone()));
+                    acceptors.insert(address, task);
+                }
+                Err(e) => {
+                    log::error!("Bind failed for {}: {}", address, e);
+                    state.set_listener(&address, ListenerStatus::Failed(e.to_string()));
+                }
+            }
+        }
+    }
+
+    async fn accept_loop(endpoint: Endpoint, address: String, accepted_tx: mpsc::Sender<Accepted>) {
+        loop {
+            match endpoint.accept().await {
+                Ok((socket, peer)) => {
+                    if accepted_tx.send((socket, peer, address.clone())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    log::error!("Accept error on {}: {}", address, e);
+                }
+            }
+        }
+    }
+
+    /// Aborts an accept task and waits for it, so its socket is closed on return.
+    async fn stop(acceptor: JoinHandle<()>) {
+        acceptor.abort();
+        let _ = acceptor.await;
+    }
+
+    /// Reads the PROXY header when `peer` is a trusted proxy and returns the client
+    /// address to use from then on.
+    async fn client_addr<S>(
+        socket: &mut S,
+        peer: SocketAddr,
+        config: &Config,
+        read_timeout: Duration,
+    ) -> Result<SocketAddr, VoteHandlerError>
+    where
+        S: AsyncRead + Unpin,
+    {
+        if !config.is_trusted_proxy(peer.ip()) {
+            return Ok(peer);
+        }
+
+        let client = tokio::time::timeout(read_timeout, proxy_protocol::read_header(socket))
+            .await
+            .unwrap_or(Err(VoteHandlerError::Timeout))?;
+        if config.debug {
+            match client {
+                Some(client) => log::debug!("Proxy {} forwarded connection from {}", peer, client),
+                None => log::debug!("Proxy {} connected on its own behalf", peer),
+            }
+        }
+        Ok(client.unwrap_or(peer))
+    }
+
+    async fn handle_connection(
+        active: ActiveConnection,
+        mut socket: Box<dyn VoteStream>,
+        peer: Peer,
+        listener: Arc<ListenerConfig>,
+    ) {
+        let state = &active.0;
+
+        // Snapshot of the settings at accept time; a reload doesn't affect this vote.
+        let config = state.config();
+        let debug = config.debug;
+        let read_timeout = Duration::from_secs(config.read_timeout_secs);
+
+        // Unix socket peers are local processes: no PROXY header, IP lists or rate limit.
+        let addr = match peer {
+            Peer::Tcp(peer) => match Self::client_addr(&mut socket, peer, &config, read_timeout).await {
+                Ok(addr) => {
+                    if addr != peer {
+                        VoteStats::inc(&state.stats.proxied);
+                    }
+                    if !Intake::admit(state, &config, addr) {
+                        return;
+                    }
+                    Peer::Tcp(addr)
+                }
+                Err(e) => {
+                    VoteStats::inc(&state.stats.errors);
+                    log::warn!("Bad PROXY header from {}: {}", peer, e);
+                    return;
+                }
+            },
+            unix => unix,
+        };
+        if debug {
+            log::debug!("Accepted vote connection from {}", addr);
+        }
+
+        let challenge = HMAC::random_string();
+        let result = tokio::time::timeout(
+            read_timeout,
+            VoteHandler::handle(&mut socket, &challenge, &state.keys, &state.tokens, &listener.scope, debug),
+        )
+        .await
+        .unwrap_or(Err(VoteHandlerError::Timeout));
+
+        let _ = Intake::deliver(state, &config, &addr, result);
+    }
+}
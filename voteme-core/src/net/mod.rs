@@ -0,0 +1,11 @@
+pub mod dedupe;
+pub mod endpoint;
+pub mod forwarded;
+pub mod http;
+pub mod intake;
+pub mod ip_filter;
+pub mod listener;
+pub mod proxy_protocol;
+pub mod rate_limit;
+pub mod stream;
+pub mod vote_handler;
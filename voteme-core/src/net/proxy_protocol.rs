@@ -0,0 +1,181 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::net::vote_handler::VoteHandlerError;
+
+/// First 12 bytes of every PROXY protocol v2 header.
+pub const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+
+/// Longest possible v1 header, CRLF included.
+const V1_MAX_LEN: usize = 107;
+
+/// Reads a HAProxy PROXY protocol v1 or v2 header and returns the client address
+/// it carries. `None` means the proxy connected on its own behalf (`LOCAL` or
+/// `UNKNOWN`, e.g. a health check), so the peer address stays as is.
+///
+/// Only the header itself is consumed; the Votifier exchange follows on the same stream.
+pub async fn read_header<S>(socket: &mut S) -> Result<Option<SocketAddr>, VoteHandlerError>
+where
+    S: AsyncRead + Unpin,
+{
+    // The shortest v1 header, "PROXY UNKNOWN\r\n", is longer than the v2 signature.
+    let mut start = [0u8; 12];
+    socket.read_exact(&mut start).await?;
+
+    if start == V2_SIGNATURE {
+        read_v2(socket).await
+    } else if start.starts_with(b"PROXY ") {
+        read_v1(socket, &start).await
+    } else {
+        Err(invalid("missing PROXY protocol header"))
+    }
+}
+
+/// `PROXY TCP4 <src> <dst> <sport> <dport>\r\n`
+async fn read_v1<S>(socket: &mut S, start: &[u8]) -> Result<Option<SocketAddr>, VoteHandlerError>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut line = start.to_vec();
+    while !line.ends_with(b"\r\n") {
+        if line.len() >= V1_MAX_LEN {
+            return Err(invalid("PROXY v1 header too long"));
+        }
+        line.push(socket.read_u8().await?);
+    }
+
+    let line = std::str::from_utf8(&line[..line.len() - 2]).map_err(|_| invalid("PROXY v1 header is not ASCII"))?;
+    parse_v1(line)
+}
+
+fn parse_v1(line: &str) -> Result<Option<SocketAddr>, VoteHandlerError> {
+    let parts: Vec<&str> = line.split(' ').collect();
+    match parts.as_slice() {
+        ["PROXY", "UNKNOWN", ..] => Ok(None),
+        ["PROXY", family @ ("TCP4" | "TCP6"), src, _dst, sport, _dport] => {
+            let ip: IpAddr = src.parse().map_err(|_| invalid("bad PROXY v1 source address"))?;
+            if ip.is_ipv4() != (*family == "TCP4") {
+                return Err(invalid("PROXY v1 address does not match its family"));
+            }
+            let port: u16 = sport.parse().map_err(|_| invalid("bad PROXY v1 source port"))?;
+            Ok(Some(SocketAddr::new(ip, port)))
+        }
+        _ => Err(invalid("malformed PROXY v1 header")),
+    }
+}
+
+/// Signature, version/command byte, family byte, u16 length, then the addresses and TLVs.
+async fn read_v2<S>(socket: &mut S) -> Result<Option<SocketAddr>, VoteHandlerError>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut header = [0u8; 4];
+    socket.read_exact(&mut header).await?;
+    let [ver_cmd, family, len_hi, len_lo] = header;
+
+    let mut body = vec![0u8; u16::from_be_bytes([len_hi, len_lo]) as usize];
+    socket.read_exact(&mut body).await?;
+
+    parse_v2(ver_cmd, family, &body)
+}
+
+fn parse_v2(ver_cmd: u8, family: u8, body: &[u8]) -> Result<Option<SocketAddr>, VoteHandlerError> {
+    if ver_cmd >> 4 != 2 {
+        return Err(invalid("unsupported PROXY protocol version"));
+    }
+    match ver_cmd & 0x0F {
+        0x0 => return Ok(None),
+        0x1 => {}
+        _ => return Err(invalid("unsupported PROXY v2 command")),
+    }
+
+    // High nibble is the address family; the transport (TCP or UDP) doesn't matter here.
+    match family >> 4 {
+        0x1 => {
+            let addr: &[u8; 12] = body
+                .get(..12)
+                .and_then(|b| b.try_into().ok())
+                .ok_or_else(|| invalid("truncated PROXY v2 IPv4 address"))?;
+            let ip = Ipv4Addr::new(addr[0], addr[1], addr[2], addr[3]);
+            let port = u16::from_be_bytes([addr[8], addr[9]]);
+            Ok(Some(SocketAddr::new(IpAddr::V4(ip), port)))
+        }
+        0x2 => {
+            let addr: &[u8; 36] = body
+                .get(..36)
+                .and_then(|b| b.try_into().ok())
+                .ok_or_else(|| invalid("truncated PROXY v2 IPv6 address"))?;
+            let mut src = [0u8; 16];
+            src.copy_from_slice(&addr[..16]);
+            let port = u16::from_be_bytes([addr[32], addr[33]]);
+            Ok(Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(src)), port)))
+        }
+        // UNSPEC or a Unix socket: no IP to report.
+        _ => Ok(None),
+    }
+}
+
+fn invalid(msg: &str) -> VoteHandlerError {
+    VoteHandlerError::InvalidPacket(msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read(bytes: Vec<u8>) -> (Result<Option<SocketAddr>, VoteHandlerError>, Vec<u8>) {
+        let mut stream = bytes.as_slice();
+        let result = read_header(&mut stream).await;
+        (result, stream.to_vec())
+    }
+
+    #[tokio::test]
+    async fn v1_header_yields_client_address() {
+        let mut bytes = b"PROXY TCP4 198.51.100.22 203.0.113.7 35646 8192\r\n".to_vec();
+        bytes.extend_from_slice(b"\x73\x3a");
+
+        let (result, rest) = read(bytes).await;
+        assert_eq!(result.unwrap(), Some("198.51.100.22:35646".parse().unwrap()));
+        assert_eq!(rest, b"\x73\x3a");
+
+        let (result, _) = read(b"PROXY TCP6 2001:db8::1 2001:db8::2 4000 8192\r\n".to_vec()).await;
+        assert_eq!(result.unwrap(), Some("[2001:db8::1]:4000".parse().unwrap()));
+
+        let (result, _) = read(b"PROXY UNKNOWN\r\n".to_vec()).await;
+        assert_eq!(result.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn v2_header_yields_client_address() {
+        let mut bytes = V2_SIGNATURE.to_vec();
+        // PROXY command, TCP over IPv4, 12 address bytes plus a 3-byte TLV.
+        bytes.extend_from_slice(&[0x21, 0x11, 0x00, 15]);
+        bytes.extend_from_slice(&[198, 51, 100, 22, 203, 0, 113, 7]);
+        bytes.extend_from_slice(&35646u16.to_be_bytes());
+        bytes.extend_from_slice(&8192u16.to_be_bytes());
+        bytes.extend_from_slice(&[0x04, 0x00, 0x00]);
+        bytes.extend_from_slice(b"rest");
+
+        let (result, rest) = read(bytes).await;
+        assert_eq!(result.unwrap(), Some("198.51.100.22:35646".parse().unwrap()));
+        assert_eq!(rest, b"rest");
+
+        let mut local = V2_SIGNATURE.to_vec();
+        local.extend_from_slice(&[0x20, 0x00, 0x00, 0x00]);
+        let (result, _) = read(local).await;
+        assert_eq!(result.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_or_malformed_headers() {
+        let (result, _) = read(vec![0u8; 256]).await;
+        assert!(matches!(result, Err(VoteHandlerError::InvalidPacket(_))));
+
+        let (result, _) = read(b"PROXY TCP4 2001:db8::1 203.0.113.7 1 2\r\n".to_vec()).await;
+        assert!(matches!(result, Err(VoteHandlerError::InvalidPacket(_))));
+
+        let (result, _) = read([b"PROXY TCP4 ".as_slice(), &[b'1'; 120]].concat()).await;
+        assert!(matches!(result, Err(VoteHandlerError::InvalidPacket(_))));
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::file::Config;
+
+/// Per-IP limits, taken from the config each time they're applied.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimits {
+    /// Connections refilled per minute; `0` disables rate limiting.
+    pub per_minute: u32,
+    /// Connections allowed back to back before the refill rate applies.
+    pub burst: u32,
+    /// Invalid packets in a row before the address is banned; `0` disables bans.
+    pub ban_after: u32,
+    pub ban_duration: Duration,
+}
+
+impl RateLimits {
+    pub fn from_config(config: &Config) -> Self {
+        RateLimits {
+            per_minute: config.rate_limit_per_minute,
+            burst: config.rate_limit_burst.max(1),
+            ban_after: config.ban_after_invalid,
+            ban_duration: Duration::from_secs(config.ban_minutes * 60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    Allowed,
+    RateLimited,
+    Banned(Duration),
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    strikes: u32,
+    banned_until: Option<Instant>,
+}
+
+/// Token bucket per peer address, plus temporary bans after repeated invalid packets.
+#[derive(Default)]
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes a token for a new connection from `ip`.
+    pub fn check(&self, ip: IpAddr, limits: &RateLimits, now: Instant) -> Admission {
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(ip).or_insert_with(|| Bucket {
+            tokens: limits.burst as f64,
+            refilled_at: now,
+            strikes: 0,
+            banned_until: None,
+        });
+
+        if let Some(until) = bucket.banned_until {
+            if until > now {
+                return Admission::Banned(until - now);
+            }
+            bucket.banned_until = None;
+            bucket.strikes = 0;
+        }
+
+        if limits.per_minute == 0 {
+            return Admission::Allowed;
+        }
+
+        let elapsed = now.saturating_duration_since(bucket.refilled_at).as_secs_f64();
+        let refill = elapsed * limits.per_minute as f64 / 60.0;
+        bucket.tokens = (bucket.tokens + refill).min(limits.burst as f64);
+        bucket.refilled_at = now;
+
+        if bucket.tokens < 1.0 {
+            return Admission::RateLimited;
+        }
+        bucket.tokens -= 1.0;
+        Admission::Allowed
+    }
+
+    /// Records an invalid packet from `ip`. Returns `true` when this strike bans it.
+    pub fn strike(&self, ip: IpAddr, limits: &RateLimits, now: Instant) -> bool {
+        if limits.ban_after == 0 {
+            return false;
+        }
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let Some(bucket) = buckets.get_mut(&ip) else {
+            return false;
+        };
+
+        bucket.strikes += 1;
+        if bucket.strikes < limits.ban_after {
+            return false;
+        }
+        bucket.strikes = 0;
+        bucket.banned_until = Some(now + limits.ban_duration);
+        true
+    }
+
+    /// A valid vote clears the invalid packet count.
+    pub fn forgive(&self, ip: IpAddr) {
+        if let Some(bucket) = self.buckets.lock().unwrap().get_mut(&ip) {
+            bucket.strikes = 0;
+        }
+    }
+
+    /// Addresses currently banned, with the time left on each ban.
+    pub fn banned(&self, now: Instant) -> Vec<(IpAddr, Duration)> {
+        self.buckets
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(ip, b)| b.banned_until.filter(|until| *until > now).map(|until| (*ip, until - now)))
+            .collect()
+    }
+
+    /// Drops buckets that are full again and carry no strikes or ban.
+    pub fn prune(&self, limits: &RateLimits, now: Instant) {
+        let full_after = if limits.per_minute == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(limits.burst as f64 * 60.0 / limits.per_minute as f64)
+        };
+
+        self.buckets.lock().unwrap().retain(|_, b| {
+            b.strikes > 0
+                || b.banned_until.is_some_and(|until| until > now)
+                || now.saturating_duration_since(b.refilled_at) < full_after
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: RateLimits = RateLimits {
+        per_minute: 60,
+        burst: 2,
+        ban_after: 3,
+        ban_duration: Duration::from_secs(600),
+    };
+
+    fn ip() -> IpAddr {
+        "203.0.113.7".parse().unwrap()
+    }
+
+    #[test]
+    fn bucket_allows_burst_then_refills() {
+        let limiter = RateLimiter::new();
+        let start = Instant::now();
+
+        assert_eq!(limiter.check(ip(), &LIMITS, start), Admission::Allowed);
+        assert_eq!(limiter.check(ip(), &LIMITS, start), Admission::Allowed);
+        assert_eq!(limiter.check(ip(), &LIMITS, start), Admission::RateLimited);
+
+        // 60 per minute refills one token a second.
+        let later = start + Duration::from_secs(1);
+        assert_eq!(limiter.check(ip(), &LIMITS, later), Admission::Allowed);
+        assert_eq!(limiter.check(ip(), &LIMITS, later), Admission::RateLimited);
+    }
+
+    #[test]
+    fn repeated_strikes_ban_until_expiry() {
+        let limiter = RateLimiter::new();
+        let start = Instant::now();
+        limiter.check(ip(), &LIMITS, start);
+
+        assert!(!limiter.strike(ip(), &LIMITS, start));
+        assert!(!limiter.strike(ip(), &LIMITS, start));
+        assert!(limiter.strike(ip(), &LIMITS, start));
+
+        let during = start + Duration::from_secs(60);
+        assert_eq!(limiter.check(ip(), &LIMITS, during), Admission::Banned(Duration::from_secs(540)));
+        assert_eq!(limiter.banned(during).len(), 1);
+
+        let after = start + Duration::from_secs(601);
+        assert_eq!(limiter.check(ip(), &LIMITS, after), Admission::Allowed);
+        assert!(limiter.banned(after).is_empty());
+    }
+
+    #[test]
+    fn valid_vote_resets_strikes() {
+        let limiter = RateLimiter::new();
+        let start = Instant::now();
+        limiter.check(ip(), &LIMITS, start);
+
+        limiter.strike(ip(), &LIMITS, start);
+        limiter.strike(ip(), &LIMITS, start);
+        limiter.forgive(ip());
+        assert!(!limiter.strike(ip(), &LIMITS, start));
+    }
+}
@@ -0,0 +1,473 @@
+use crate::crypto::{CryptoError, KeyManager, HMAC, RSA};
+use crate::file::TokenStore;
+use crate::net::stream::PeekableStream;
+use crate::parser::vote_parser::VoteParser;
+
+use rsa::traits::PublicKeyParts;
+use rsa::RsaPrivateKey;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::time::Instant;
+use voteme_api::Vote;
+
+#[derive(Debug)]
+pub enum VoteHandlerError {
+    Io(std::io::Error),
+    InvalidUtf8(std::string::FromUtf8Error),
+    Crypto(CryptoError),
+    InvalidPacket(String),
+    UnknownService(String),
+    InvalidSignature,
+    ChallengeMismatch,
+    /// Generic v1 rejection; the reason is deliberately not exposed.
+    Rejected,
+    /// The sender didn't deliver its vote within `read_timeout_secs`.
+    Timeout,
+    /// The vote's service doesn't accept votes from this address.
+    Filtered(String),
+    /// The same vote was already delivered recently; carries the username.
+    Duplicate(String),
+}
+
+impl std::fmt::Display for VoteHandlerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VoteHandlerError::Io(e) => write!(f, "io error: {e}"),
+            VoteHandlerError::InvalidUtf8(e) => write!(f, "invalid utf8: {e}"),
+            VoteHandlerError::Crypto(e) => write!(f, "crypto error: {e}"),
+            VoteHandlerError::InvalidPacket(msg) => write!(f, "invalid packet: {msg}"),
+            VoteHandlerError::UnknownService(name) => write!(f, "unknown service: {name}"),
+            VoteHandlerError::InvalidSignature => write!(f, "signature is not valid"),
+            VoteHandlerError::ChallengeMismatch => write!(f, "challenge is not valid"),
+            VoteHandlerError::Rejected => write!(f, "vote rejected"),
+            VoteHandlerError::Timeout => write!(f, "timed out waiting for the vote"),
+            VoteHandlerError::Filtered(reason) => write!(f, "address refused: {reason}"),
+            VoteHandlerError::Duplicate(username) => write!(f, "duplicate vote for {username}"),
+        }
+    }
+}
+
+impl std::error::Error for VoteHandlerError {}
+
+impl VoteHandlerError {
+    /// Short error class reported to v2 senders in the `cause` field.
+    pub fn cause(&self) -> &'static str {
+        match self {
+            VoteHandlerError::Io(_) => "IOException",
+            VoteHandlerError::InvalidUtf8(_) | VoteHandlerError::InvalidPacket(_) => "CorruptedFrameException",
+            VoteHandlerError::Crypto(_) => "DecryptionException",
+            VoteHandlerError::UnknownService(_) => "UnknownServiceException",
+            VoteHandlerError::InvalidSignature => "InvalidSignatureException",
+            VoteHandlerError::ChallengeMismatch => "InvalidChallengeException",
+            VoteHandlerError::Rejected => "RejectedVoteException",
+            VoteHandlerError::Timeout => "SocketTimeoutException",
+            VoteHandlerError::Filtered(_) => "RejectedVoteException",
+            VoteHandlerError::Duplicate(_) => "DuplicateVoteException",
+        }
+    }
+}
+
+impl From<std::io::Error> for VoteHandlerError {
+    fn from(value: std::io::Error) -> Self {
+        VoteHandlerError::Io(value)
+    }
+}
+
+impl From<std::string::FromUtf8Error> for VoteHandlerError {
+    fn from(value: std::string::FromUtf8Error) -> Self {
+        VoteHandlerError::InvalidUtf8(value)
+    }
+}
+
+impl From<CryptoError> for VoteHandlerError {
+    fn from(value: CryptoError) -> Self {
+        VoteHandlerError::Crypto(value)
+    }
+}
+
+/// Magic prefix of every NuVotifier v2 frame.
+pub const V2_MAGIC: u16 = 0x733A;
+
+/// Time every rejected v1 block is held for, so rejection reasons can't be told apart by timing.
+const V1_REJECT_DELAY: Duration = Duration::from_millis(100);
+
+/// How long to wait for the rest of a block when keys of different sizes are active.
+const V1_EXTRA_READ: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    V1,
+    V2,
+}
+
+impl std::fmt::Display for Protocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Protocol::V1 => write!(f, "v1"),
+            Protocol::V2 => write!(f, "v2"),
+        }
+    }
+}
+
+impl Protocol {
+    /// Tells the formats apart from the first two bytes sent by the peer.
+    /// Anything that isn't the v2 magic is treated as a v1 RSA block.
+    pub fn detect(prefix: &[u8]) -> Option<Protocol> {
+        match prefix {
+            [hi, lo, ..] if u16::from_be_bytes([*hi, *lo]) == V2_MAGIC => Some(Protocol::V2),
+            [_, _, ..] => Some(Protocol::V1),
+            _ => None,
+        }
+    }
+}
+
+/// What a listener accepts: which protocols, and votes for which services.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoteScope {
+    #[serde(default = "default_protocols")]
+    pub protocols: Vec<Protocol>,
+    /// Services whose votes and tokens this listener accepts; empty means all.
+    #[serde(default)]
+    pub services: Vec<String>,
+}
+
+fn default_protocols() -> Vec<Protocol> {
+    vec![Protocol::V1, Protocol::V2]
+}
+
+impl Default for VoteScope {
+    fn default() -> Self {
+        VoteScope {
+            protocols: default_protocols(),
+            services: Vec::new(),
+        }
+    }
+}
+
+impl VoteScope {
+    pub fn allows_protocol(&self, protocol: Protocol) -> bool {
+        self.protocols.contains(&protocol)
+    }
+
+    pub fn allows_service(&self, service: &str) -> bool {
+        self.services.is_empty() || self.services.iter().any(|s| s == service)
+    }
+
+    fn check_service(&self, vote: Vote) -> Result<Vote, VoteHandlerError> {
+        if self.allows_service(&vote.service_name) {
+            Ok(vote)
+        } else {
+            Err(VoteHandlerError::UnknownService(vote.service_name))
+        }
+    }
+}
+
+pub struct VoteHandler;
+
+impl VoteHandler {
+    /// Greets the peer once, then hands an untouched stream to the detected protocol.
+    pub async fn handle<S>(
+        socket: S,
+        challenge: &str,
+        keys: &KeyManager,
+        tokens: &TokenStore,
+        scope: &VoteScope,
+        debug: bool,
+    ) -> Result<Vote, VoteHandlerError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let mut socket = PeekableStream::new(socket);
+        socket.write_all(format!("VOTIFIER 2 {}\n", challenge).as_bytes()).await?;
+
+        let prefix = socket.peek(2).await?;
+        let Some(protocol) = Protocol::detect(prefix) else {
+            return Err(VoteHandlerError::InvalidPacket("Connection closed before vote".to_string()));
+        };
+        if !scope.allows_protocol(protocol) {
+            return Err(VoteHandlerError::InvalidPacket(format!(
+                "{protocol} votes are not accepted on this listener"
+            )));
+        }
+
+        match protocol {
+            Protocol::V1 => Self::handle_v1(&mut socket, keys, scope, debug).await,
+            Protocol::V2 => Self::handle_v2(&mut socket, challenge, tokens, scope).await,
+        }
+    }
+
+    /// Votifier v1. Every rejection looks the same to the peer: the full block is
+    /// read, the connection is held until a fixed deadline, and one generic error
+    /// is returned. The detailed reason is only logged locally in debug mode.
+    pub async fn handle_v1<S>(
+        socket: &mut S,
+        keys: &KeyManager,
+        scope: &VoteScope,
+        debug: bool,
+    ) -> Result<Vote, VoteHandlerError>
+    where
+        S: AsyncRead + Unpin,
+    {
+        let keys = keys.decryption_keys();
+        let min_size = keys.iter().map(|k| k.private.size()).min().unwrap_or_default();
+        let max_size = keys.iter().map(|k| k.private.size()).max().unwrap_or_default();
+
+        let mut rsa_block = vec![0u8; min_size];
+        let mut read = socket.read_exact(&mut rsa_block).await.map(|_| ());
+        if read.is_ok() && max_size > min_size {
+            read = Self::read_extra(socket, &mut rsa_block, max_size).await;
+        }
+        let deadline = Instant::now() + V1_REJECT_DELAY;
+
+        let mut result = Err(VoteHandlerError::InvalidPacket(format!(
+            "No {}-bit key for this block",
+            rsa_block.len() * 8
+        )));
+        if let Err(e) = read {
+            result = Err(e.into());
+        } else {
+            for key in keys.iter().filter(|k| k.private.size() == rsa_block.len()) {
+                result = Self::decrypt_v1(&rsa_block, &key.private);
+                if result.is_ok() {
+                    log::info!("v1 vote decrypted with key {}", key.label());
+                    break;
+                }
+            }
+        }
+
+        match result.and_then(|vote| scope.check_service(vote)) {
+            Ok(vote) => Ok(vote),
+            Err(e) => {
+                if debug {
+                    log::debug!("v1 vote rejected: {}", e);
+                }
+                tokio::time::sleep_until(deadline).await;
+                Err(VoteHandlerError::Rejected)
+            }
+        }
+    }
+
+    /// Reads on towards `max_size` bytes until the peer stops sending.
+    async fn read_extra<S>(socket: &mut S, block: &mut Vec<u8>, max_size: usize) -> std::io::Result<()>
+    where
+        S: AsyncRead + Unpin,
+    {
+        let deadline = Instant::now() + V1_EXTRA_READ;
+        let mut filled = block.len();
+        block.resize(max_size, 0);
+
+        while filled < max_size {
+            match tokio::time::timeout_at(deadline, socket.read(&mut block[filled..])).await {
+                Ok(Ok(0)) | Err(_) => break,
+                Ok(Ok(n)) => filled += n,
+                Ok(Err(e)) => return Err(e),
+            }
+        }
+
+        block.truncate(filled);
+        Ok(())
+    }
+
+    fn decrypt_v1(rsa_block: &[u8], key: &RsaPrivateKey) -> Result<Vote, VoteHandlerError> {
+        let mut decrypted = RSA::decrypt(rsa_block, key)?;
+
+        if let Some(nul) = decrypted.iter().position(|&b| b == 0) {
+            decrypted.truncate(nul);
+        }
+
+        let plaintext = String::from_utf8(decrypted)?;
+
+        VoteParser::parse_v1(&plaintext)
+    }
+
+    /// NuVotifier v2: `0x733A` magic, u16 length, then a signed JSON message.
+    /// Always answers the sender with a status JSON line.
+    pub async fn handle_v2<S>(
+        socket: &mut S,
+        challenge: &str,
+        tokens: &TokenStore,
+        scope: &VoteScope,
+    ) -> Result<Vote, VoteHandlerError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let result = Self::read_v2(socket, challenge, tokens, scope).await;
+
+        let response = match &result {
+            Ok(_) => serde_json::json!({ "status": "ok" }),
+            Err(e) => serde_json::json!({
+                "status": "error",
+                "cause": e.cause(),
+                "error": e.to_string(),
+            }),
+        };
+        let _ = socket.write_all(format!("{response}\r\n").as_bytes()).await;
+
+        result
+    }
+
+    async fn read_v2<S>(
+        socket: &mut S,
+        challenge: &str,
+        tokens: &TokenStore,
+        scope: &VoteScope,
+    ) -> Result<Vote, VoteHandlerError>
+    where
+        S: AsyncRead + Unpin,
+    {
+        let mut header = [0u8; 4];
+        socket.read_exact(&mut header).await?;
+
+        let magic = u16::from_be_bytes([header[0], header[1]]);
+        if magic != V2_MAGIC {
+            return Err(VoteHandlerError::InvalidPacket(format!("Bad v2 magic: {magic:#06x}")));
+        }
+
+        let len = u16::from_be_bytes([header[2], header[3]]) as usize;
+        let mut buf = vec![0u8; len];
+        socket.read_exact(&mut buf).await?;
+
+        let json = String::from_utf8(buf)?;
+        let message = VoteParser::parse_v2_message(&json)?;
+        let (vote, vote_challenge) = VoteParser::parse_v2(&message.payload)?;
+        let vote = scope.check_service(vote)?;
+
+        let token = tokens
+            .get(&vote.service_name)
+            .ok_or_else(|| VoteHandlerError::UnknownService(vote.service_name.clone()))?;
+
+        if !HMAC::verify(message.payload.as_bytes(), &message.signature, &token) {
+            return Err(VoteHandlerError::InvalidSignature);
+        }
+
+        if vote_challenge != challenge {
+            return Err(VoteHandlerError::ChallengeMismatch);
+        }
+
+        Ok(vote)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::RSAIO::KeyFormat;
+    use crate::file::tokens::Tokens;
+    use rsa::pkcs8::DecodePrivateKey;
+    use tokio::io::AsyncBufReadExt;
+
+    const V1_CAPTURE: &[u8] = include_bytes!("../../tests/fixtures/v1_vote.bin");
+    const V2_CAPTURE: &[u8] = include_bytes!("../../tests/fixtures/v2_vote.bin");
+    const TEST_KEY: &str = include_str!("../../tests/fixtures/test_private.pem");
+
+    /// Challenge and token the v2 capture was signed with.
+    const V2_CHALLENGE: &str = "testchallenge";
+    const V2_TOKEN: &str = "testtoken";
+
+    fn test_tokens() -> TokenStore {
+        let tokens = Tokens {
+            fallback_to_default: false,
+            tokens: [("TestList".to_string(), V2_TOKEN.to_string())].into(),
+        };
+        TokenStore::from_tokens("tokens.toml", tokens)
+    }
+
+    async fn run_capture(capture: Vec<u8>, challenge: &str) -> (Result<Vote, VoteHandlerError>, Vec<String>) {
+        run_scoped_capture(capture, challenge, &VoteScope::default()).await
+    }
+
+    /// Plays `capture` as the client and returns the handler result plus everything it sent back.
+    async fn run_scoped_capture(
+        capture: Vec<u8>,
+        challenge: &str,
+        scope: &VoteScope,
+    ) -> (Result<Vote, VoteHandlerError>, Vec<String>) {
+        let key = RsaPrivateKey::from_pkcs8_pem(TEST_KEY).unwrap();
+        let keys = KeyManager::from_key(key, KeyFormat::Pem, Duration::from_secs(3600));
+        let tokens = test_tokens();
+        let (server, client) = tokio::io::duplex(4096);
+
+        let client = tokio::spawn(async move {
+            let (read, mut write) = tokio::io::split(client);
+            write.write_all(&capture).await.unwrap();
+            let mut lines = tokio::io::BufReader::new(read).lines();
+            let mut received = Vec::new();
+            while let Ok(Some(line)) = lines.next_line().await {
+                received.push(line);
+            }
+            received
+        });
+
+        let result = VoteHandler::handle(server, challenge, &keys, &tokens, scope, false).await;
+        (result, client.await.unwrap())
+    }
+
+    #[test]
+    fn detects_captures() {
+        assert_eq!(Protocol::detect(V1_CAPTURE), Some(Protocol::V1));
+        assert_eq!(Protocol::detect(V2_CAPTURE), Some(Protocol::V2));
+        assert_eq!(Protocol::detect(&V2_CAPTURE[..1]), None);
+    }
+
+    #[tokio::test]
+    async fn v1_capture_is_decoded_after_single_greeting() {
+        let (result, received) = run_capture(V1_CAPTURE.to_vec(), V2_CHALLENGE).await;
+        let vote = result.unwrap();
+
+        assert_eq!(vote.service_name, "TestList");
+        assert_eq!(vote.username, "Steve");
+        assert_eq!(received, vec![format!("VOTIFIER 2 {V2_CHALLENGE}")]);
+    }
+
+    #[tokio::test]
+    async fn v2_capture_is_verified() {
+        let (result, received) = run_capture(V2_CAPTURE.to_vec(), V2_CHALLENGE).await;
+        let vote = result.unwrap();
+
+        assert_eq!(vote.service_name, "TestList");
+        assert_eq!(vote.username, "Alex");
+        assert_eq!(received[1], r#"{"status":"ok"}"#);
+    }
+
+    #[tokio::test]
+    async fn v2_capture_with_wrong_challenge_is_rejected() {
+        let (result, received) = run_capture(V2_CAPTURE.to_vec(), "otherchallenge").await;
+
+        assert!(matches!(result, Err(VoteHandlerError::ChallengeMismatch)));
+        assert!(received[1].contains(r#""status":"error""#));
+    }
+
+    #[tokio::test]
+    async fn tampered_v1_block_gets_generic_rejection() {
+        let mut capture = V1_CAPTURE.to_vec();
+        capture[10] ^= 0xFF;
+
+        let (result, received) = run_capture(capture, V2_CHALLENGE).await;
+
+        assert!(matches!(result, Err(VoteHandlerError::Rejected)));
+        assert_eq!(received, vec![format!("VOTIFIER 2 {V2_CHALLENGE}")]);
+    }
+
+    #[tokio::test]
+    async fn scope_limits_protocols_and_services() {
+        let v2_only = VoteScope {
+            protocols: vec![Protocol::V2],
+            services: Vec::new(),
+        };
+        let (result, _) = run_scoped_capture(V1_CAPTURE.to_vec(), V2_CHALLENGE, &v2_only).await;
+        assert!(matches!(result, Err(VoteHandlerError::InvalidPacket(_))));
+
+        let other_service = VoteScope {
+            services: vec!["OtherList".to_string()],
+            ..VoteScope::default()
+        };
+        let (result, received) = run_scoped_capture(V2_CAPTURE.to_vec(), V2_CHALLENGE, &other_service).await;
+        assert!(matches!(result, Err(VoteHandlerError::UnknownService(_))));
+        assert!(received[1].contains("UnknownServiceException"));
+
+        let (result, _) = run_scoped_capture(V1_CAPTURE.to_vec(), V2_CHALLENGE, &other_service).await;
+        assert!(matches!(result, Err(VoteHandlerError::Rejected)));
+    }
+}
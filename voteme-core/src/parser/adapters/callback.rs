@@ -0,0 +1,71 @@
+use hyper::Method;
+use voteme_api::{Vote, VoteProtocol};
+
+use crate::crypto::HMAC;
+use crate::file::config::HookConfig;
+use crate::net::vote_handler::VoteHandlerError;
+use crate::parser::http_adapter::{required, HttpAdapter, HttpRequest};
+
+/// Vote lists that call a URL with the player in the query string or a form body,
+/// plus a shared secret. Field names are set with the `username_field`
+/// (`username`), `address_field` (`address`) and `secret_field` (`secret`) options.
+pub struct CallbackAdapter;
+
+impl HttpAdapter for CallbackAdapter {
+    fn name(&self) -> &'static str {
+        "callback"
+    }
+
+    fn accepts(&self, method: &Method) -> bool {
+        *method == Method::GET || *method == Method::POST
+    }
+
+    fn parse(&self, request: &HttpRequest, hook: &HookConfig, token: &str) -> Result<Vote, VoteHandlerError> {
+        let option = |name: &str, default: &'static str| hook.options.get(name).map(String::as_str).unwrap_or(default);
+        let fields = request.fields();
+
+        let secret = fields.get(option("secret_field", "secret"));
+        if !secret.is_some_and(|secret| HMAC::secret_matches(secret, token)) {
+            return Err(VoteHandlerError::InvalidSignature);
+        }
+
+        Ok(Vote::received(
+            hook.service.clone(),
+            required(&fields, option("username_field", "username"))?,
+            fields.get(option("address_field", "address")).cloned().unwrap_or_default(),
+            VoteProtocol::Http,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::adapters::fixture::{hook, Fixture, TOKEN};
+
+    const CAPTURE: &str = include_str!("../../../tests/fixtures/hooks/callback.http");
+
+    fn serverlist_hook() -> HookConfig {
+        hook(
+            "callback",
+            &[("username_field", "user"), ("address_field", "ip"), ("secret_field", "key")],
+        )
+    }
+
+    #[test]
+    fn query_capture_is_accepted() {
+        let fixture = Fixture::parse(CAPTURE);
+        let vote = CallbackAdapter.parse(&fixture.request(), &serverlist_hook(), TOKEN).unwrap();
+
+        assert_eq!(vote.service_name, "TestList");
+        assert_eq!(vote.username, "Steve");
+        assert_eq!(vote.address, "203.0.113.50");
+    }
+
+    #[test]
+    fn default_field_names_miss_the_secret() {
+        let fixture = Fixture::parse(CAPTURE);
+        let result = CallbackAdapter.parse(&fixture.request(), &hook("callback", &[]), TOKEN);
+        assert!(matches!(result, Err(VoteHandlerError::InvalidSignature)));
+    }
+}
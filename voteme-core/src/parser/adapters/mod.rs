@@ -0,0 +1,86 @@
+//! Built-in webhook adapters. A new vote list needs a file here implementing
+//! [`HttpAdapter`], an entry in [`BUILTIN`], and a captured request under
+//! `tests/fixtures/hooks/` for its test.
+
+use crate::parser::http_adapter::HttpAdapter;
+
+pub mod callback;
+pub mod topgg;
+pub mod voteme;
+
+pub static BUILTIN: &[&dyn HttpAdapter] = &[
+    &voteme::VoteMeAdapter,
+    &callback::CallbackAdapter,
+    &topgg::TopGgAdapter,
+];
+
+#[cfg(test)]
+pub(crate) mod fixture {
+    use std::collections::BTreeMap;
+
+    use hyper::header::{HeaderMap, HeaderName, HeaderValue};
+    use hyper::{Method, Uri};
+
+    use crate::file::config::HookConfig;
+    use crate::parser::http_adapter::HttpRequest;
+
+    /// Token the fixtures are signed with.
+    pub const TOKEN: &str = "testtoken";
+
+    /// A raw HTTP request captured into `tests/fixtures/hooks/*.http`.
+    pub struct Fixture {
+        method: Method,
+        uri: Uri,
+        headers: HeaderMap,
+        body: Vec<u8>,
+    }
+
+    impl Fixture {
+        pub fn parse(raw: &str) -> Self {
+            let raw = raw.replace("\r\n", "\n");
+            let (head, body) = raw.split_once("\n\n").unwrap_or((&raw, ""));
+            let mut lines = head.lines();
+
+            let mut request_line = lines.next().unwrap().split(' ');
+            let method = request_line.next().unwrap().parse().unwrap();
+            let uri = request_line.next().unwrap().parse().unwrap();
+
+            let mut headers = HeaderMap::new();
+            for line in lines {
+                let (name, value) = line.split_once(':').unwrap();
+                headers.insert(
+                    HeaderName::from_bytes(name.trim().as_bytes()).unwrap(),
+                    HeaderValue::from_str(value.trim()).unwrap(),
+                );
+            }
+
+            Fixture {
+                method,
+                uri,
+                headers,
+                body: body.strip_suffix('\n').unwrap_or(body).as_bytes().to_vec(),
+            }
+        }
+
+        pub fn request(&self) -> HttpRequest<'_> {
+            HttpRequest {
+                method: &self.method,
+                uri: &self.uri,
+                headers: &self.headers,
+                body: &self.body,
+            }
+        }
+    }
+
+    pub fn hook(adapter: &str, options: &[(&str, &str)]) -> HookConfig {
+        HookConfig {
+            path: format!("/hooks/{adapter}"),
+            adapter: adapter.to_string(),
+            service: "TestList".to_string(),
+            options: options
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<BTreeMap<_, _>>(),
+        }
+    }
+}
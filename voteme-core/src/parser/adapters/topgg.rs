@@ -0,0 +1,82 @@
+use hyper::header::AUTHORIZATION;
+use serde::Deserialize;
+use voteme_api::{Vote, VoteProtocol};
+
+use crate::crypto::HMAC;
+use crate::file::config::HookConfig;
+use crate::net::vote_handler::VoteHandlerError;
+use crate::parser::http_adapter::{HttpAdapter, HttpRequest};
+
+/// top.gg webhooks: the token in `Authorization` and a JSON body whose `user`
+/// is the voter's Discord ID. Test votes from the dashboard are rejected
+/// unless the hook sets `accept_test = "true"`, and then arrive as
+/// [`VoteProtocol::Test`].
+pub struct TopGgAdapter;
+
+#[derive(Deserialize)]
+struct TopGgVote {
+    user: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+impl HttpAdapter for TopGgAdapter {
+    fn name(&self) -> &'static str {
+        "topgg"
+    }
+
+    fn parse(&self, request: &HttpRequest, hook: &HookConfig, token: &str) -> Result<Vote, VoteHandlerError> {
+        let authorization = request.header(AUTHORIZATION.as_str());
+        if !authorization.is_some_and(|secret| HMAC::secret_matches(secret, token)) {
+            return Err(VoteHandlerError::InvalidSignature);
+        }
+
+        let body: TopGgVote = serde_json::from_slice(request.body)
+            .map_err(|e| VoteHandlerError::InvalidPacket(format!("Invalid JSON: {}", e)))?;
+        let protocol = match body.kind.as_str() {
+            "upvote" => VoteProtocol::Http,
+            "test" if hook.options.get("accept_test").is_some_and(|accept| accept == "true") => VoteProtocol::Test,
+            "test" => return Err(VoteHandlerError::InvalidPacket("Test votes are not accepted".to_string())),
+            kind => return Err(VoteHandlerError::InvalidPacket(format!("Unknown vote type {:?}", kind))),
+        };
+
+        Ok(Vote::received(hook.service.clone(), body.user, "", protocol))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::adapters::fixture::{hook, Fixture, TOKEN};
+
+    const CAPTURE: &str = include_str!("../../../tests/fixtures/hooks/topgg.http");
+    const TEST_CAPTURE: &str = include_str!("../../../tests/fixtures/hooks/topgg_test.http");
+
+    #[test]
+    fn upvote_capture_is_accepted() {
+        let fixture = Fixture::parse(CAPTURE);
+        let vote = TopGgAdapter.parse(&fixture.request(), &hook("topgg", &[]), TOKEN).unwrap();
+
+        assert_eq!(vote.service_name, "TestList");
+        assert_eq!(vote.username, "395526710101278721");
+        assert_eq!(vote.protocol, VoteProtocol::Http);
+    }
+
+    #[test]
+    fn test_votes_need_accept_test() {
+        let fixture = Fixture::parse(TEST_CAPTURE);
+        let result = TopGgAdapter.parse(&fixture.request(), &hook("topgg", &[]), TOKEN);
+        assert!(matches!(result, Err(VoteHandlerError::InvalidPacket(_))));
+
+        let vote = TopGgAdapter.parse(&fixture.request(), &hook("topgg", &[("accept_test", "true")]), TOKEN).unwrap();
+        assert_eq!(vote.username, "395526710101278721");
+        assert_eq!(vote.protocol, VoteProtocol::Test);
+    }
+
+    #[test]
+    fn wrong_authorization_is_rejected() {
+        let fixture = Fixture::parse(CAPTURE);
+        let result = TopGgAdapter.parse(&fixture.request(), &hook("topgg", &[]), "othertoken");
+        assert!(matches!(result, Err(VoteHandlerError::InvalidSignature)));
+    }
+}
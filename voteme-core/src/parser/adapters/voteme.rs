@@ -0,0 +1,77 @@
+use hyper::header::HeaderMap;
+use voteme_api::Vote;
+
+use crate::crypto::HMAC;
+use crate::file::config::{HookConfig, HttpAuth};
+use crate::net::http::{SIGNATURE_HEADER, TOKEN_HEADER};
+use crate::net::vote_handler::VoteHandlerError;
+use crate::parser::http_adapter::{HttpAdapter, HttpRequest};
+use crate::parser::vote_parser::VoteParser;
+
+/// VoteMe's own format, the one `/vote` takes: JSON or form fields named like the
+/// v2 payload, signed per the `auth` option (`hmac` by default, or `secret`).
+pub struct VoteMeAdapter;
+
+impl HttpAdapter for VoteMeAdapter {
+    fn name(&self) -> &'static str {
+        "voteme"
+    }
+
+    fn parse(&self, request: &HttpRequest, hook: &HookConfig, token: &str) -> Result<Vote, VoteHandlerError> {
+        let auth = match hook.options.get("auth").map(String::as_str) {
+            None | Some("hmac") => HttpAuth::Hmac,
+            Some("secret") => HttpAuth::Secret,
+            Some(other) => return Err(VoteHandlerError::InvalidPacket(format!("Unknown auth option {other:?}"))),
+        };
+        authenticate(request.headers, request.body, auth, token)?;
+
+        let mut vote = VoteParser::parse_http(request.content_type(), request.body)?;
+        vote.service_name = hook.service.clone();
+        Ok(vote)
+    }
+}
+
+/// Checks the `X-VoteMe-Signature` or `X-VoteMe-Token` header against `token`.
+pub fn authenticate(headers: &HeaderMap, body: &[u8], auth: HttpAuth, token: &str) -> Result<(), VoteHandlerError> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    let authentic = match auth {
+        HttpAuth::Hmac => header(SIGNATURE_HEADER).is_some_and(|sig| HMAC::verify_header(body, sig, token)),
+        HttpAuth::Secret => header(TOKEN_HEADER).is_some_and(|secret| HMAC::secret_matches(secret, token)),
+    };
+
+    if authentic {
+        Ok(())
+    } else {
+        Err(VoteHandlerError::InvalidSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::adapters::fixture::{hook, Fixture, TOKEN};
+    use voteme_api::Timestamp;
+
+    const CAPTURE: &str = include_str!("../../../tests/fixtures/hooks/voteme.http");
+
+    #[test]
+    fn signed_capture_is_accepted() {
+        let fixture = Fixture::parse(CAPTURE);
+        let vote = VoteMeAdapter
+            .parse(&fixture.request(), &hook("voteme", &[]), TOKEN)
+            .unwrap();
+
+        assert_eq!(vote.service_name, "TestList");
+        assert_eq!(vote.username, "Alex");
+        assert_eq!(vote.address, "198.51.100.9");
+        assert_eq!(vote.timestamp, Timestamp::from_millis(1_700_000_000_000));
+    }
+
+    #[test]
+    fn wrong_token_is_rejected() {
+        let fixture = Fixture::parse(CAPTURE);
+        let result = VoteMeAdapter.parse(&fixture.request(), &hook("voteme", &[]), "othertoken");
+        assert!(matches!(result, Err(VoteHandlerError::InvalidSignature)));
+    }
+}
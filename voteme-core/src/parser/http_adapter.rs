@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use hyper::header::{HeaderMap, CONTENT_TYPE};
+use hyper::{Method, Uri};
+use voteme_api::Vote;
+
+use crate::file::config::HookConfig;
+use crate::net::vote_handler::VoteHandlerError;
+use crate::parser::adapters;
+
+/// A webhook request as handed to an [`HttpAdapter`], body already read.
+pub struct HttpRequest<'a> {
+    pub method: &'a Method,
+    pub uri: &'a Uri,
+    pub headers: &'a HeaderMap,
+    pub body: &'a [u8],
+}
+
+impl HttpRequest<'_> {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    pub fn content_type(&self) -> &str {
+        self.header(CONTENT_TYPE.as_str()).unwrap_or_default()
+    }
+
+    /// Query string parameters merged with form-encoded body fields; the body wins.
+    pub fn fields(&self) -> HashMap<String, String> {
+        let mut fields: HashMap<String, String> = self
+            .uri
+            .query()
+            .and_then(|q| serde_urlencoded::from_str(q).ok())
+            .unwrap_or_default();
+
+        if self.content_type().starts_with("application/x-www-form-urlencoded")
+            && let Ok(form) = serde_urlencoded::from_bytes::<HashMap<String, String>>(self.body)
+        {
+            fields.extend(form);
+        }
+        fields
+    }
+}
+
+/// Turns one vote list's webhook request into a [`Vote`]. Adapters are picked per
+/// path by `[[http.hooks]]` entries; see [`adapters::BUILTIN`].
+pub trait HttpAdapter: Send + Sync {
+    /// Name used as `adapter = "..."` in the config.
+    fn name(&self) -> &'static str;
+
+    fn accepts(&self, method: &Method) -> bool {
+        *method == Method::POST
+    }
+
+    /// Decodes and authenticates `request`. `token` is the token of the hook's
+    /// service; the returned vote carries `hook.service` as its service name.
+    fn parse(&self, request: &HttpRequest, hook: &HookConfig, token: &str) -> Result<Vote, VoteHandlerError>;
+}
+
+/// Looks up a built-in adapter by its config name.
+pub fn adapter(name: &str) -> Option<&'static dyn HttpAdapter> {
+    adapters::BUILTIN.iter().copied().find(|a| a.name() == name)
+}
+
+/// Required field, as an `InvalidPacket` error when missing or empty.
+pub fn required(fields: &HashMap<String, String>, name: &str) -> Result<String, VoteHandlerError> {
+    fields
+        .get(name)
+        .filter(|v| !v.is_empty())
+        .cloned()
+        .ok_or_else(|| VoteHandlerError::InvalidPacket(format!("Missing field {name:?}")))
+}
@@ -0,0 +1,3 @@
+pub mod adapters;
+pub mod http_adapter;
+pub mod vote_parser;
@@ -0,0 +1,136 @@
+use crate::net::vote_handler::{VoteHandlerError};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+use voteme_api::{Timestamp, Vote, VoteProtocol};
+pub struct VoteParser;
+
+impl VoteParser {
+    /// Parse a Votifier v1 by tcp payload (after decryption).
+    pub fn parse_v1(plaintext: &str) -> Result<Vote, VoteHandlerError> {
+        let mut lines = plaintext.lines();
+
+        let header = lines
+            .next()
+            .ok_or_else(|| VoteHandlerError::InvalidPacket("Empty payload".to_string()))?;
+
+        if header != "VOTE" {
+            return Err(VoteHandlerError::InvalidPacket(format!(
+                "Unexpected vote header: {:?}",
+                header
+            )));
+        }
+
+        let service_name = lines
+            .next()
+            .ok_or_else(|| VoteHandlerError::InvalidPacket("Missing service name".to_string()))?
+            .to_string();
+
+        let username = lines
+            .next()
+            .ok_or_else(|| VoteHandlerError::InvalidPacket("Missing username".to_string()))?
+            .to_string();
+
+        let address = lines
+            .next()
+            .ok_or_else(|| VoteHandlerError::InvalidPacket("Missing address".to_string()))?
+            .to_string();
+
+        let timestamp_str = lines
+            .next()
+            .ok_or_else(|| VoteHandlerError::InvalidPacket("Missing timestamp".to_string()))?;
+
+        let timestamp = Timestamp::parse(timestamp_str)
+            .ok_or_else(|| VoteHandlerError::InvalidPacket("Invalid timestamp".to_string()))?;
+
+        Ok(Vote::new(service_name, username, address, timestamp, VoteProtocol::V1))
+    }
+
+    /// Parse the outer NuVotifier v2 message (`{"payload": ..., "signature": ...}`).
+    pub fn parse_v2_message(json: &str) -> Result<V2Message, VoteHandlerError> {
+        serde_json::from_str(json).map_err(|e| {
+            VoteHandlerError::InvalidPacket(format!("Invalid JSON: {}", e))
+        })
+    }
+
+    /// Parse a Votifier v2 JSON payload (after signature check).
+    /// Returns the vote together with the challenge it was signed for.
+    pub fn parse_v2(json: &str) -> Result<(Vote, String), VoteHandlerError> {
+        #[derive(Deserialize)]
+        struct V2Payload {
+            #[serde(rename = "serviceName")]
+            service_name: String,
+            username: String,
+            address: String,
+            timestamp: Timestamp,
+            #[serde(default)]
+            challenge: String,
+            #[serde(rename = "additionalData", default)]
+            additional_data: Map<String, Value>,
+        }
+
+        let payload: V2Payload = serde_json::from_str(json).map_err(|e| {
+            VoteHandlerError::InvalidPacket(format!("Invalid JSON: {}", e))
+        })?;
+
+        let mut vote = Vote::new(
+            payload.service_name,
+            payload.username,
+            payload.address,
+            payload.timestamp,
+            VoteProtocol::V2,
+        );
+        vote.additional_data = payload.additional_data;
+
+        Ok((vote, payload.challenge))
+    }
+
+    /// Parse a vote POSTed to the HTTP receiver, as JSON or form fields named like
+    /// the v2 payload. A missing timestamp means "now"; a missing `serviceName` is
+    /// left empty for the caller to fill in or reject.
+    pub fn parse_http(content_type: &str, body: &[u8]) -> Result<Vote, VoteHandlerError> {
+        #[derive(Deserialize)]
+        struct HttpVote {
+            #[serde(rename = "serviceName", default)]
+            service_name: String,
+            username: String,
+            #[serde(default)]
+            address: String,
+            timestamp: Option<Timestamp>,
+        }
+
+        let media_type = content_type.split(';').next().unwrap_or_default().trim();
+        let vote: HttpVote = match media_type {
+            "application/json" => serde_json::from_slice(body)
+                .map_err(|e| VoteHandlerError::InvalidPacket(format!("Invalid JSON: {}", e)))?,
+            "application/x-www-form-urlencoded" => serde_urlencoded::from_bytes(body)
+                .map_err(|e| VoteHandlerError::InvalidPacket(format!("Invalid form: {}", e)))?,
+            other => {
+                return Err(VoteHandlerError::InvalidPacket(format!(
+                    "Unsupported content type: {:?}",
+                    other
+                )));
+            }
+        };
+
+        Ok(match vote.timestamp {
+            Some(timestamp) => Vote::new(vote.service_name, vote.username, vote.address, timestamp, VoteProtocol::Http),
+            None => Vote::received(vote.service_name, vote.username, vote.address, VoteProtocol::Http),
+        })
+    }
+}
+
+/// Signed NuVotifier v2 wrapper. `payload` is itself a JSON string.
+#[derive(Debug, Deserialize)]
+pub struct V2Message {
+    pub payload: String,
+    pub signature: String,
+}
+
+/// Current Unix time in milliseconds.
+pub fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
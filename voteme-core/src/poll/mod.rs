@@ -0,0 +1,314 @@
+//! Vote lists that don't push votes: each `[[pollers]]` entry is asked on its
+//! own schedule and new votes are emitted like received ones.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tokio::time::MissedTickBehavior;
+
+use crate::file::config::{PollKind, PollerConfig};
+use crate::parser::vote_parser::now_millis;
+use crate::state::{VoteMeState, VoteStats};
+
+pub mod provider;
+pub mod seen;
+
+pub use seen::SeenStore;
+
+/// Names of the players `has_voted` pollers ask about.
+pub trait PlayerSource: Send + Sync {
+    fn online_players(&self) -> Pin<Box<dyn Future<Output = Vec<String>> + Send + '_>>;
+}
+
+/// Runs one task per configured poller and restarts them when `pollers` changes.
+pub struct Poller;
+
+impl Poller {
+    pub async fn run(
+        state: Arc<VoteMeState>,
+        seen: Arc<SeenStore>,
+        players: Arc<dyn PlayerSource>,
+        mut shutdown: watch::Receiver<bool>,
+    ) {
+        let mut configs_rx = state.subscribe_pollers();
+        let mut tasks = JoinSet::new();
+
+        loop {
+            let configs = configs_rx.borrow_and_update().clone();
+            let timeout = Duration::from_secs(state.config.read().unwrap().read_timeout_secs);
+            match provider::client(timeout) {
+                Ok(client) => {
+                    for config in configs {
+                        log::info!("Polling {} every {}s", config.name, config.interval_secs);
+                        tasks.spawn(Self::poll_loop(
+                            state.clone(),
+                            seen.clone(),
+                            players.clone(),
+                            client.clone(),
+                            config,
+                        ));
+                    }
+                }
+                Err(e) if !configs.is_empty() => log::error!("Pollers disabled: {}", e),
+                Err(_) => {}
+            }
+
+            tokio::select! {
+                _ = shutdown.changed() => break,
+                changed = configs_rx.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                }
+            }
+            tasks.shutdown().await;
+        }
+
+        tasks.shutdown().await;
+    }
+
+    async fn poll_loop(
+        state: Arc<VoteMeState>,
+        seen: Arc<SeenStore>,
+        players: Arc<dyn PlayerSource>,
+        client: Client,
+        config: PollerConfig,
+    ) {
+        let mut ticks = tokio::time::interval(Duration::from_secs(config.interval_secs.max(1)));
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticks.tick().await;
+            if let Err(e) = Self::poll(&state, &seen, players.as_ref(), &client, &config).await {
+                VoteStats::inc(&state.stats.poll_errors);
+                log::warn!("Poller {} failed: {}", config.name, e);
+            }
+        }
+    }
+
+    /// One round: fetch, keep what hasn't been reported, save that, then emit it.
+    /// Saving first means a crash can lose a vote but never repeats one; a
+    /// failed save emits nothing and leaves the votes for the next round.
+    pub async fn poll(
+        state: &VoteMeState,
+        seen: &SeenStore,
+        players: &dyn PlayerSource,
+        client: &Client,
+        config: &PollerConfig,
+    ) -> Result<usize, String> {
+        let now = now_millis();
+        let window_ms = config.remember_hours as i64 * 3_600_000;
+
+        let found = match config.kind {
+            PollKind::Recent => provider::fetch_recent(client, config, now).await?,
+            PollKind::HasVoted => {
+                let online = players.online_players().await;
+                provider::fetch_voted(client, config, &online, now).await?
+            }
+        };
+
+        let fresh: Vec<_> = found
+            .into_iter()
+            .filter(|polled| polled.cast_at.is_none_or(|at| now - at.as_millis() < window_ms))
+            .filter(|polled| seen.first_report(&config.name, &polled.key, now, window_ms))
+            .collect();
+        seen.prune(&config.name, now, window_ms);
+        if !fresh.is_empty()
+            && let Err(e) = seen.save()
+        {
+            // Unsaved votes stay unreported, so the next poll tries them again.
+            for polled in &fresh {
+                seen.forget(&config.name, &polled.key);
+            }
+            return Err(e);
+        }
+
+        let debug = state.config.read().unwrap().debug;
+        let dedupe_window = Duration::from_secs(state.config.read().unwrap().dedupe_window_secs);
+        for polled in &fresh {
+            if let Err(e) = provider::claim(client, config, &polled.vote.username).await {
+                log::warn!("Poller {} could not claim the vote of {}: {}", config.name, polled.vote.username, e);
+            }
+            if !state.dedupe.first_seen(&polled.vote, dedupe_window, Instant::now()) {
+                VoteStats::inc(&state.stats.duplicates);
+                continue;
+            }
+
+            VoteStats::inc(&state.stats.polled);
+            if debug {
+                log::debug!("Polled vote from {}: {}", config.name, polled.vote.username);
+            }
+            state.vote_service.emit(polled.vote.clone());
+        }
+        Ok(fresh.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyManager, RSAIO::KeyFormat};
+    use crate::file::tokens::Tokens;
+    use crate::file::{Config, TokenStore};
+    use rsa::pkcs8::DecodePrivateKey;
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use voteme_api::{Vote, VoteService};
+
+    const TEST_KEY: &str = include_str!("../../tests/fixtures/test_private.pem");
+
+    struct Online(Vec<String>);
+
+    impl PlayerSource for Online {
+        fn online_players(&self) -> Pin<Box<dyn Future<Output = Vec<String>> + Send + '_>> {
+            Box::pin(async move { self.0.clone() })
+        }
+    }
+
+    /// Answers every request with `body` and records the request lines.
+    async fn mock_server(body: String) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                let head = String::from_utf8_lossy(&buf[..n]);
+                recorded.lock().unwrap().push(head.lines().next().unwrap_or_default().to_string());
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        (format!("http://{address}"), requests)
+    }
+
+    fn test_state() -> (Arc<VoteMeState>, Arc<Mutex<Vec<Vote>>>) {
+        let key = rsa::RsaPrivateKey::from_pkcs8_pem(TEST_KEY).unwrap();
+        let keys = Arc::new(KeyManager::from_key(key, KeyFormat::Pem, Duration::from_secs(3600)));
+        let tokens = Tokens {
+            fallback_to_default: false,
+            tokens: Default::default(),
+        };
+        let tokens = Arc::new(TokenStore::from_tokens("tokens.toml", tokens));
+
+        let service = Arc::new(VoteService::new());
+        let votes = Arc::new(Mutex::new(Vec::new()));
+        let received = votes.clone();
+        service.on_vote(move |vote| received.lock().unwrap().push(vote));
+
+        let state = VoteMeState::new(Config::default(), keys, tokens, service);
+        (Arc::new(state), votes)
+    }
+
+    fn poller(name: &str, kind: PollKind, url: String) -> PollerConfig {
+        PollerConfig {
+            name: name.to_string(),
+            service: "TestList".to_string(),
+            kind,
+            url,
+            api_key: "secret key".to_string(),
+            headers: Default::default(),
+            interval_secs: 60,
+            remember_hours: 24,
+            claim_url: None,
+            fields: Default::default(),
+        }
+    }
+
+    fn state_file(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("voteme-{name}-{}.json", std::process::id()));
+        path.to_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn recent_votes_are_emitted_once_across_restarts() {
+        let recent = format!(
+            r#"{{"votes":[{{"id":7,"username":"Alex","timestamp":{}}},{{"id":8,"username":"Steve","timestamp":1}}]}}"#,
+            now_millis() / 1000
+        );
+        let (base, requests) = mock_server(recent).await;
+        let (state, votes) = test_state();
+        let config = poller("recent", PollKind::Recent, format!("{base}/votes?key={{key}}"));
+        let client = provider::client(Duration::from_secs(5)).unwrap();
+        let state_file = state_file("poll-recent");
+        let players = Online(Vec::new());
+
+        let seen = SeenStore::new(&state_file);
+        assert_eq!(Poller::poll(&state, &seen, &players, &client, &config).await, Ok(1));
+        assert_eq!(Poller::poll(&state, &seen, &players, &client, &config).await, Ok(0));
+
+        let restarted = SeenStore::new(&state_file);
+        restarted.load().unwrap();
+        assert_eq!(Poller::poll(&state, &restarted, &players, &client, &config).await, Ok(0));
+
+        // Steve's vote is from 1970, outside `remember_hours`.
+        let votes = votes.lock().unwrap();
+        assert_eq!(votes.len(), 1);
+        assert_eq!(votes[0].username, "Alex");
+        assert_eq!(votes[0].service_name, "TestList");
+        assert!(requests.lock().unwrap()[0].starts_with("GET /votes?key=secret%20key "));
+
+        std::fs::remove_file(state_file).unwrap();
+    }
+
+    #[tokio::test]
+    async fn votes_are_kept_for_the_next_round_when_the_seen_file_cannot_be_saved() {
+        let recent = format!(r#"{{"votes":[{{"id":7,"username":"Alex","timestamp":{}}}]}}"#, now_millis() / 1000);
+        let (base, _requests) = mock_server(recent).await;
+        let (state, votes) = test_state();
+        let config = poller("recent", PollKind::Recent, format!("{base}/votes?key={{key}}"));
+        let client = provider::client(Duration::from_secs(5)).unwrap();
+        let players = Online(Vec::new());
+
+        // A plain file where the state directory should be makes the save fail.
+        let blocker = state_file("poll-unwritable");
+        std::fs::write(&blocker, "").unwrap();
+        let seen = SeenStore::new(&format!("{blocker}/seen.json"));
+
+        assert!(Poller::poll(&state, &seen, &players, &client, &config).await.is_err());
+        assert!(votes.lock().unwrap().is_empty());
+        assert_eq!(seen.len("recent"), 0);
+
+        std::fs::remove_file(&blocker).unwrap();
+        assert_eq!(Poller::poll(&state, &seen, &players, &client, &config).await, Ok(1));
+        assert_eq!(votes.lock().unwrap().len(), 1);
+
+        std::fs::remove_dir_all(blocker).unwrap();
+    }
+
+    #[tokio::test]
+    async fn has_voted_asks_about_online_players_and_claims() {
+        let (base, requests) = mock_server(r#"{"voted":1}"#.to_string()).await;
+        let (state, votes) = test_state();
+        let mut config = poller("hasvoted", PollKind::HasVoted, format!("{base}/check?user={{username}}"));
+        config.claim_url = Some(format!("{base}/claim?user={{username}}"));
+        let client = provider::client(Duration::from_secs(5)).unwrap();
+        let state_file = state_file("poll-hasvoted");
+        let players = Online(vec!["Alex".to_string()]);
+
+        let seen = SeenStore::new(&state_file);
+        assert_eq!(Poller::poll(&state, &seen, &players, &client, &config).await, Ok(1));
+        assert_eq!(Poller::poll(&state, &seen, &players, &client, &config).await, Ok(0));
+
+        assert_eq!(votes.lock().unwrap().len(), 1);
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("GET /check?user=Alex "));
+        assert!(requests[1].starts_with("GET /claim?user=Alex "));
+
+        std::fs::remove_file(state_file).unwrap();
+    }
+}
@@ -0,0 +1,164 @@
+use std::time::Duration;
+
+use reqwest::{Client, RequestBuilder};
+use serde_json::Value;
+use voteme_api::{Timestamp, Vote, VoteProtocol};
+
+use crate::file::config::{PollFields, PollerConfig};
+
+/// A vote found by a poller, with the key it's remembered by.
+pub struct PolledVote {
+    pub key: String,
+    pub vote: Vote,
+    /// When the vote was cast, if the API said.
+    pub cast_at: Option<Timestamp>,
+}
+
+pub fn client(timeout: Duration) -> Result<Client, String> {
+    Client::builder()
+        .timeout(timeout)
+        .user_agent(concat!("VoteMe/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))
+}
+
+/// Asks a `recent` provider for its vote list.
+pub async fn fetch_recent(client: &Client, config: &PollerConfig, now: i64) -> Result<Vec<PolledVote>, String> {
+    let body = send(request(client, config, &config.url, None)).await?;
+    let json: Value = serde_json::from_str(&body).map_err(|e| format!("Invalid JSON: {e}"))?;
+    recent_votes(config, &json, now)
+}
+
+/// Asks a `has_voted` provider about each player and returns those who voted.
+pub async fn fetch_voted(
+    client: &Client,
+    config: &PollerConfig,
+    players: &[String],
+    now: i64,
+) -> Result<Vec<PolledVote>, String> {
+    let mut voted = Vec::new();
+    for player in players {
+        let body = send(request(client, config, &config.url, Some(player))).await?;
+        if has_voted(&config.fields, &body) {
+            voted.push(PolledVote {
+                key: player.clone(),
+                vote: Vote::new(
+                    config.service.clone(),
+                    player.clone(),
+                    "",
+                    Timestamp::from_millis(now),
+                    VoteProtocol::Poll,
+                ),
+                cast_at: None,
+            });
+        }
+    }
+    Ok(voted)
+}
+
+/// Tells a `has_voted` provider the vote was handed out, if it has a claim URL.
+pub async fn claim(client: &Client, config: &PollerConfig, username: &str) -> Result<(), String> {
+    match &config.claim_url {
+        Some(url) => send(request(client, config, url, Some(username))).await.map(drop),
+        None => Ok(()),
+    }
+}
+
+/// Reads the votes out of a `recent` answer.
+pub fn recent_votes(config: &PollerConfig, json: &Value, now: i64) -> Result<Vec<PolledVote>, String> {
+    let fields = &config.fields;
+    let list = lookup(json, &fields.votes)
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("No vote list at {:?}", fields.votes))?;
+
+    let mut votes = Vec::new();
+    for entry in list {
+        let Some(username) = lookup(entry, &fields.username).and_then(text).filter(|u| !u.is_empty()) else {
+            continue;
+        };
+        let raw_timestamp = lookup(entry, &fields.timestamp).and_then(text);
+        let cast_at = raw_timestamp.as_deref().and_then(Timestamp::parse);
+
+        let key = match lookup(entry, &fields.id).and_then(text) {
+            Some(id) => format!("id:{id}"),
+            None => format!("{}@{}", username, raw_timestamp.unwrap_or_default()),
+        };
+
+        votes.push(PolledVote {
+            key,
+            vote: Vote::new(
+                config.service.clone(),
+                username,
+                lookup(entry, &fields.address).and_then(text).unwrap_or_default(),
+                cast_at.unwrap_or(Timestamp::from_millis(now)),
+                VoteProtocol::Poll,
+            ),
+            cast_at,
+        });
+    }
+    Ok(votes)
+}
+
+/// Whether a `has_voted` answer says yes: a plain `1`/`true`, or a JSON object
+/// whose `voted` field is.
+pub fn has_voted(fields: &PollFields, body: &str) -> bool {
+    let body = body.trim();
+    let flag = match serde_json::from_str::<Value>(body) {
+        Ok(json @ Value::Object(_)) => lookup(&json, &fields.voted).and_then(text),
+        Ok(json) => text(&json),
+        Err(_) => Some(body.to_string()),
+    };
+    matches!(flag.as_deref(), Some("1" | "true"))
+}
+
+fn request(client: &Client, config: &PollerConfig, url: &str, username: Option<&str>) -> RequestBuilder {
+    let mut request = client.get(expand(url, config, username));
+    for (name, value) in &config.headers {
+        request = request.header(name, expand(value, config, username));
+    }
+    request
+}
+
+/// Errors leave out the URL, which carries the expanded `{key}`.
+async fn send(request: RequestBuilder) -> Result<String, String> {
+    let response = request.send().await.map_err(|e| e.without_url().to_string())?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("HTTP {status}"));
+    }
+    response.text().await.map_err(|e| e.without_url().to_string())
+}
+
+/// Fills in `{key}` and `{username}`, percent-encoded.
+fn expand(template: &str, config: &PollerConfig, username: Option<&str>) -> String {
+    template
+        .replace("{key}", &encode(&config.api_key))
+        .replace("{username}", &encode(username.unwrap_or_default()))
+}
+
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+fn lookup<'a>(json: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return Some(json);
+    }
+    path.split('.').try_fold(json, |value, key| value.get(key))
+}
+
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+pub const DEFAULT_POLL_STATE_PATH: &str = "plugins/VoteMe/poll_state.json";
+
+/// Votes each poller already reported, by provider name, with the time they were
+/// reported in milliseconds. Saved after every poll so restarts don't repeat them.
+pub struct SeenStore {
+    state_file: String,
+    providers: Mutex<BTreeMap<String, BTreeMap<String, i64>>>,
+}
+
+impl SeenStore {
+    pub fn new(state_file: &str) -> Self {
+        SeenStore {
+            state_file: state_file.to_string(),
+            providers: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn new_default() -> Self {
+        Self::new(DEFAULT_POLL_STATE_PATH)
+    }
+
+    /// Reads the state file if there is one.
+    pub fn load(&self) -> Result<(), String> {
+        if !Path::new(&self.state_file).exists() {
+            return Ok(());
+        }
+
+        let state_str = fs::read_to_string(&self.state_file)
+            .map_err(|e| format!("Failed to read poll state {}: {e}", self.state_file))?;
+        let providers = serde_json::from_str(&state_str)
+            .map_err(|e| format!("Invalid poll state {}: {e}", self.state_file))?;
+        *self.providers.lock().unwrap() = providers;
+        Ok(())
+    }
+
+    /// Writes to a temporary file first so a crash mid-write keeps the old state.
+    pub fn save(&self) -> Result<(), String> {
+        let state_path = Path::new(&self.state_file);
+
+        if let Some(parent) = state_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+
+        let state_str = serde_json::to_string(&*self.providers.lock().unwrap()).map_err(|e| e.to_string())?;
+        let temp_path = state_path.with_extension("json.tmp");
+        fs::write(&temp_path, state_str)
+            .and_then(|_| fs::rename(&temp_path, state_path))
+            .map_err(|e| format!("Failed to write poll state {}: {e}", self.state_file))
+    }
+
+    /// Records `key` for `provider` and returns `false` if it was already
+    /// reported within `window_ms`.
+    pub fn first_report(&self, provider: &str, key: &str, now: i64, window_ms: i64) -> bool {
+        let mut providers = self.providers.lock().unwrap();
+        let seen = providers.entry(provider.to_string()).or_default();
+        match seen.get(key) {
+            Some(at) if now - at < window_ms => false,
+            _ => {
+                seen.insert(key.to_string(), now);
+                true
+            }
+        }
+    }
+
+    /// Takes back a [`SeenStore::first_report`] whose vote was never handed out.
+    pub fn forget(&self, provider: &str, key: &str) {
+        if let Some(seen) = self.providers.lock().unwrap().get_mut(provider) {
+            seen.remove(key);
+        }
+    }
+
+    /// Forgets `provider`'s entries older than `window_ms`.
+    pub fn prune(&self, provider: &str, now: i64, window_ms: i64) {
+        if let Some(seen) = self.providers.lock().unwrap().get_mut(provider) {
+            seen.retain(|_, at| now - *at < window_ms);
+        }
+    }
+
+    pub fn len(&self, provider: &str) -> usize {
+        self.providers
+            .lock()
+            .unwrap()
+            .get(provider)
+            .map_or(0, BTreeMap::len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 3_600_000;
+
+    #[test]
+    fn reported_votes_survive_a_restart() {
+        let state_file = std::env::temp_dir().join(format!("voteme-poll-state-{}.json", std::process::id()));
+        let state_file = state_file.to_str().unwrap();
+
+        let store = SeenStore::new(state_file);
+        assert!(store.first_report("list", "Alex", 0, HOUR));
+        assert!(!store.first_report("list", "Alex", HOUR / 2, HOUR));
+        store.save().unwrap();
+
+        let restarted = SeenStore::new(state_file);
+        restarted.load().unwrap();
+        assert!(!restarted.first_report("list", "Alex", HOUR / 2, HOUR));
+        assert!(restarted.first_report("other", "Alex", HOUR / 2, HOUR));
+
+        restarted.prune("list", HOUR * 2, HOUR);
+        assert_eq!(restarted.len("list"), 0);
+        assert!(restarted.first_report("list", "Alex", HOUR * 2, HOUR));
+
+        fs::remove_file(state_file).unwrap();
+    }
+}
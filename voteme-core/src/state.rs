@@ -0,0 +1,302 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+use voteme_api::VoteService;
+
+use crate::crypto::RSAIO::RSA_DIR;
+use crate::crypto::{key_manager, KeyManager, RSAIO};
+use crate::doctor::VoteProbe;
+use crate::file::config::{ForwardConfig, ListenerConfig, PollerConfig};
+use crate::file::{Config, ConfigManager, TokenStore};
+use crate::net::dedupe::DedupeCache;
+use crate::net::rate_limit::RateLimiter;
+
+#[derive(Debug, Clone)]
+pub enum ListenerStatus {
+    Starting,
+    Listening,
+    Failed(String),
+    Stopped,
+}
+
+impl std::fmt::Display for ListenerStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListenerStatus::Starting => write!(f, "starting"),
+            ListenerStatus::Listening => write!(f, "listening"),
+            ListenerStatus::Failed(e) => write!(f, "failed: {e}"),
+            ListenerStatus::Stopped => write!(f, "stopped"),
+        }
+    }
+}
+
+/// Where a `[[forward]]` target stands.
+#[derive(Debug, Clone, Default)]
+pub struct ForwardStatus {
+    pub queued: usize,
+    /// Why the last attempt failed; cleared by a delivery.
+    pub last_error: Option<String>,
+}
+
+impl ForwardStatus {
+    pub fn failed(queued: usize, error: String) -> Self {
+        ForwardStatus {
+            queued,
+            last_error: Some(error),
+        }
+    }
+}
+
+impl std::fmt::Display for ForwardStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} queued", self.queued)?;
+        if let Some(e) = &self.last_error {
+            write!(f, ", last error: {e}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct VoteStats {
+    pub connections: AtomicU64,
+    pub accepted: AtomicU64,
+    pub rejected: AtomicU64,
+    pub errors: AtomicU64,
+    /// Connections currently being handled.
+    pub active: AtomicU64,
+    /// Connections closed on accept because `max_connections` was reached.
+    pub over_capacity: AtomicU64,
+    /// Connections closed on accept by the per-IP rate limit or a ban.
+    pub rate_limited: AtomicU64,
+    /// Connections and votes refused by the IP allow and deny lists.
+    pub filtered: AtomicU64,
+    pub timeouts: AtomicU64,
+    /// Connections from trusted proxies that carried a client address.
+    pub proxied: AtomicU64,
+    /// Votes dropped because the same vote was delivered recently.
+    pub duplicates: AtomicU64,
+    /// Requests handled by the HTTP receiver.
+    pub http_requests: AtomicU64,
+    /// Votes found by pollers and emitted.
+    pub polled: AtomicU64,
+    pub poll_errors: AtomicU64,
+    /// Votes delivered to `[[forward]]` targets, and attempts that failed.
+    pub forwarded: AtomicU64,
+    pub forward_failures: AtomicU64,
+}
+
+impl VoteStats {
+    pub fn inc(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn dec(counter: &AtomicU64) {
+        counter.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn get(counter: &AtomicU64) -> u64 {
+        counter.load(Ordering::Relaxed)
+    }
+}
+
+/// Plugin state shared by the vote listener and the `/voteme` commands.
+pub struct VoteMeState {
+    pub config: RwLock<Config>,
+    pub keys: Arc<KeyManager>,
+    pub tokens: Arc<TokenStore>,
+    pub vote_service: Arc<VoteService>,
+    /// Sees `/voteme doctor` votes arrive.
+    pub probe: Arc<VoteProbe>,
+    /// Status of each listener, by address.
+    pub listeners: Mutex<BTreeMap<String, ListenerStatus>>,
+    /// Status of each forward target, by name.
+    pub forwarders: Mutex<BTreeMap<String, ForwardStatus>>,
+    pub stats: VoteStats,
+    pub limiter: RateLimiter,
+    pub dedupe: DedupeCache,
+    pub started_at: Instant,
+    /// Listeners that should be running; changes rebind them.
+    listener_configs: watch::Sender<Vec<ListenerConfig>>,
+    /// Address of the HTTP receiver, `None` while it's disabled.
+    http_addr: watch::Sender<Option<String>>,
+    /// Pollers that should be running; changes restart them.
+    poller_configs: watch::Sender<Vec<PollerConfig>>,
+    /// Forward targets; changes restart their senders.
+    forward_configs: watch::Sender<Vec<ForwardConfig>>,
+}
+
+impl VoteMeState {
+    pub fn new(
+        config: Config,
+        keys: Arc<KeyManager>,
+        tokens: Arc<TokenStore>,
+        vote_service: Arc<VoteService>,
+    ) -> Self {
+        let (listener_configs, _) = watch::channel(config.listeners());
+        let (http_addr, _) = watch::channel(config.http_addr());
+        let (poller_configs, _) = watch::channel(config.pollers.clone());
+        let (forward_configs, _) = watch::channel(config.forward.clone());
+        let probe = VoteProbe::attach(&vote_service);
+        VoteMeState {
+            config: RwLock::new(config),
+            keys,
+            tokens,
+            vote_service,
+            probe,
+            listeners: Mutex::new(BTreeMap::new()),
+            forwarders: Mutex::new(BTreeMap::new()),
+            stats: VoteStats::default(),
+            limiter: RateLimiter::new(),
+            dedupe: DedupeCache::new(),
+            started_at: Instant::now(),
+            listener_configs,
+            http_addr,
+            poller_configs,
+            forward_configs,
+        }
+    }
+
+    /// Reads `Config.toml`, `tokens.toml` and the RSA keys under `plugins/VoteMe`,
+    /// creating any that are missing. Accepted votes go to `vote_service`.
+    pub async fn load(vote_service: Arc<VoteService>) -> Result<Self, String> {
+        let mut config = Config::default();
+        ConfigManager::new_default().init_config(&mut config).await?;
+
+        let tokens = Arc::new(TokenStore::new_default());
+        tokens.init()?;
+
+        let passphrase = key_manager::load_passphrase(
+            &config.key_passphrase_env,
+            config.key_passphrase_file.as_deref(),
+        )
+        .map_err(|e| format!("Failed to read key passphrase: {e}"))?;
+
+        let keys = Arc::new(
+            KeyManager::init(
+                Path::new(RSA_DIR),
+                config.rsa_bits as usize,
+                config.key_format,
+                Duration::from_secs(config.key_grace_hours * 3600),
+                passphrase,
+            )
+            .map_err(|e| format!("Failed to load RSA keys: {e}"))?,
+        );
+
+        let current_key = keys.current();
+        if let Ok(line) = RSAIO::public_key_line(&current_key.public) {
+            log::info!("Public key for vote sites: {}", line);
+        }
+        log::info!("Public key fingerprint (SHA-256): {}", current_key.fingerprint);
+
+        Ok(Self::new(config, keys, tokens, vote_service))
+    }
+
+    pub fn config(&self) -> Config {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn set_listener(&self, address: &str, status: ListenerStatus) {
+        self.listeners.lock().unwrap().insert(address.to_string(), status);
+    }
+
+    pub fn remove_listener(&self, address: &str) {
+        self.listeners.lock().unwrap().remove(address);
+    }
+
+    pub fn subscribe_listeners(&self) -> watch::Receiver<Vec<ListenerConfig>> {
+        self.listener_configs.subscribe()
+    }
+
+    pub fn subscribe_http(&self) -> watch::Receiver<Option<String>> {
+        self.http_addr.subscribe()
+    }
+
+    pub fn subscribe_pollers(&self) -> watch::Receiver<Vec<PollerConfig>> {
+        self.poller_configs.subscribe()
+    }
+
+    pub fn subscribe_forwarders(&self) -> watch::Receiver<Vec<ForwardConfig>> {
+        self.forward_configs.subscribe()
+    }
+
+    pub fn set_forward_status(&self, name: &str, status: ForwardStatus) {
+        self.forwarders.lock().unwrap().insert(name.to_string(), status);
+    }
+
+    /// Re-reads `Config.toml`, `tokens.toml` and the key files. New connections
+    /// use the new settings; changed listener addresses are rebound and changed
+    /// pollers and forward targets restarted.
+    pub async fn reload(&self) -> Result<(), String> {
+        let mut config = Config::default();
+        ConfigManager::new_default().load_config(&mut config).await?;
+        self.tokens.load()?;
+        self.keys
+            .reload()
+            .map_err(|e| format!("Failed to reload RSA keys: {e}"))?;
+
+        let current_bits = self.keys.current().bits();
+        if current_bits != config.rsa_bits as usize {
+            log::warn!(
+                "rsa_bits is {} but the current key has {} bits; run /voteme rotatekey to apply it.",
+                config.rsa_bits,
+                current_bits
+            );
+        }
+
+        let listeners = config.listeners();
+        let http_addr = config.http_addr();
+        let pollers = config.pollers.clone();
+        let forward = config.forward.clone();
+        *self.config.write().unwrap() = config;
+        send_if_changed(&self.listener_configs, listeners);
+        send_if_changed(&self.http_addr, http_addr);
+        send_if_changed(&self.poller_configs, pollers);
+        send_if_changed(&self.forward_configs, forward);
+
+        log::info!("VoteMe configuration reloaded.");
+        Ok(())
+    }
+
+    /// Settings to paste into a vote site form, one per line.
+    pub fn vote_site_info(&self, service: &str) -> Vec<String> {
+        let config = self.config();
+        let key = self.keys.current();
+        let public_key = RSAIO::public_key_line(&key.public)
+            .unwrap_or_else(|e| format!("<unavailable: {e}>"));
+        let token = self
+            .tokens
+            .get(service)
+            .unwrap_or_else(|| "<none, add one with /voteme token>".to_string());
+
+        // First network listener that takes votes for this service.
+        let address = config
+            .listeners()
+            .into_iter()
+            .find(|l| !l.is_unix() && l.scope.allows_service(service))
+            .map(|l| l.address)
+            .unwrap_or_else(|| "<no listener accepts this service>".to_string());
+
+        vec![
+            format!("Address: {}", address),
+            format!("Public key ({}-bit): {}", key.bits(), public_key),
+            format!("Fingerprint (SHA-256): {}", key.fingerprint),
+            format!("Token ({}): {}", service, token),
+        ]
+    }
+}
+
+/// Only wakes subscribers when the value actually changed.
+fn send_if_changed<T: PartialEq>(tx: &watch::Sender<T>, value: T) {
+    tx.send_if_modified(|current| {
+        if *current == value {
+            return false;
+        }
+        *current = value;
+        true
+    });
+}
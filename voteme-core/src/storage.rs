@@ -0,0 +1,23 @@
+//! What VoteReward and `voteme-daemon` keep on disk.
+
+/// SQLite `votes` table shared by VoteReward's database and `voteme-daemon`'s
+/// sqlite sink, so either can read what the other wrote. Rows are unique per
+/// service, username, address and vote timestamp.
+pub const VOTES_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS votes (
+        id            INTEGER PRIMARY KEY AUTOINCREMENT,
+        service_name   TEXT NOT NULL,
+        username       TEXT NOT NULL,
+        address        TEXT,
+        vote_timestamp TEXT,
+        received_at_ms INTEGER NOT NULL,
+        vote_json      TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_votes_username ON votes(username);
+    CREATE INDEX IF NOT EXISTS idx_votes_service ON votes(service_name);
+    CREATE INDEX IF NOT EXISTS idx_votes_received ON votes(received_at_ms);
+
+    CREATE UNIQUE INDEX IF NOT EXISTS uniq_votes_natural
+    ON votes(service_name, username, IFNULL(address, ''), IFNULL(vote_timestamp, ''));
+";
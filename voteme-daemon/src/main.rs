@@ -0,0 +1,129 @@
+//! Standalone vote receiver: VoteMe's listeners, HTTP receiver, pollers and
+//! forwarding without a Minecraft server, recording each vote in SQLite or a
+//! JSONL file.
+//!
+//! ```text
+//! voteme-daemon --dir /srv/votes --sqlite votes.db
+//! voteme-daemon --dir /srv/votes --jsonl votes.jsonl
+//! ```
+//!
+//! Settings, tokens and keys live in `<dir>/plugins/VoteMe`, the plugin's own
+//! layout, so a server's folder can be pointed at as is. Votes also go to the
+//! `[[forward]]` targets in `Config.toml`.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::Parser;
+use log::LevelFilter;
+use voteme_api::VoteService;
+use voteme_core::net::listener::ListenerHandle;
+use voteme_core::poll::PlayerSource;
+use voteme_core::state::VoteMeState;
+
+mod logger;
+mod sink;
+
+use sink::jsonl::JsonlSink;
+use sink::sqlite::SqliteSink;
+use sink::VoteSink;
+
+#[derive(Parser)]
+#[command(name = "voteme-daemon", version, about = "Receive Votifier votes without a Minecraft server")]
+struct Args {
+    /// Directory holding `plugins/VoteMe`; created on first start.
+    #[arg(long, default_value = ".")]
+    dir: PathBuf,
+
+    /// SQLite database to record votes in, relative to --dir.
+    #[arg(long)]
+    sqlite: Option<PathBuf>,
+
+    /// File to append one JSON object per vote to, relative to --dir.
+    #[arg(long)]
+    jsonl: Option<PathBuf>,
+
+    /// Log debug messages too.
+    #[arg(long)]
+    debug: bool,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let args = Args::parse();
+    logger::init(if args.debug { LevelFilter::Debug } else { LevelFilter::Info });
+
+    match run(args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            log::error!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: Args) -> Result<(), String> {
+    std::fs::create_dir_all(&args.dir)
+        .and_then(|_| std::env::set_current_dir(&args.dir))
+        .map_err(|e| format!("Cannot use {}: {e}", args.dir.display()))?;
+
+    let mut sinks: Vec<Box<dyn VoteSink>> = Vec::new();
+    if let Some(path) = &args.sqlite {
+        sinks.push(Box::new(SqliteSink::open(path)?));
+    }
+    if let Some(path) = &args.jsonl {
+        sinks.push(Box::new(JsonlSink::open(path)?));
+    }
+
+    let vote_service = Arc::new(VoteService::new());
+    let state = Arc::new(VoteMeState::load(vote_service.clone()).await?);
+    if sinks.is_empty() && state.config().forward.is_empty() {
+        log::warn!("No --sqlite, --jsonl or [[forward]] target: votes are only logged.");
+    }
+    let writer = sink::attach(&vote_service, sinks)?;
+
+    let listener = ListenerHandle::start(state.clone(), Arc::new(NoPlayers))?;
+    log::info!("voteme-daemon running in {}", args.dir.display());
+
+    shutdown_signal().await?;
+    log::info!("Shutting down...");
+    let drain = Duration::from_secs(state.config().shutdown_timeout_secs);
+    listener.shutdown(drain).await;
+    if let Some(writer) = writer {
+        tokio::task::spawn_blocking(move || writer.close())
+            .await
+            .map_err(|e| format!("Vote sink writer failed: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Ctrl-C, or SIGTERM from a service manager.
+async fn shutdown_signal() -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut term = signal(SignalKind::terminate()).map_err(|e| e.to_string())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result.map_err(|e| e.to_string()),
+            _ = term.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await.map_err(|e| e.to_string())
+    }
+}
+
+/// Nobody is online without a game server, so `has_voted` pollers stay idle.
+struct NoPlayers;
+
+impl PlayerSource for NoPlayers {
+    fn online_players(&self) -> Pin<Box<dyn Future<Output = Vec<String>> + Send + '_>> {
+        Box::pin(async { Vec::new() })
+    }
+}
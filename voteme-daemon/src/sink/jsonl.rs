@@ -0,0 +1,80 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use voteme_api::Vote;
+
+use crate::sink::VoteSink;
+
+/// Appends each vote as one JSON object per line, flushed as it's written.
+pub struct JsonlSink {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl JsonlSink {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+        log::info!("Recording votes in {}", path.display());
+
+        Ok(JsonlSink {
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl VoteSink for JsonlSink {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn record(&self, vote: &Vote) -> Result<(), String> {
+        let mut line = serde_json::to_string(vote).map_err(|e| e.to_string())?;
+        line.push('\n');
+
+        let mut file = self.file.lock().unwrap();
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.flush())
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use voteme_api::{Timestamp, VoteProtocol};
+
+    #[test]
+    fn votes_are_appended_one_per_line() {
+        let path = std::env::temp_dir().join(format!("voteme-daemon-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let vote = |username: &str, received_at: i64| Vote {
+            received_at: Timestamp::from_millis(received_at),
+            ..Vote::new("TestList", username, "198.51.100.9", Timestamp::from_secs(1_700_000_000), VoteProtocol::V2)
+        };
+        let sink = JsonlSink::open(&path).unwrap();
+        sink.record(&vote("Alex", 1)).unwrap();
+        sink.record(&vote("Steve", 2)).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = contents.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["username"], "Alex");
+        assert_eq!(lines[0]["timestamp"], 1_700_000_000_000i64);
+        assert_eq!(lines[1]["received_at"], 2);
+        assert_eq!(lines[1]["protocol"], "v2");
+        let _ = fs::remove_file(&path);
+    }
+}
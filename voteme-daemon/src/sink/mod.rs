@@ -0,0 +1,107 @@
+//! Where the daemon records accepted votes.
+
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use voteme_api::{Vote, VoteService};
+use voteme_core::doctor;
+
+pub mod jsonl;
+pub mod sqlite;
+
+pub trait VoteSink: Send + Sync {
+    /// Shown in logs, e.g. the file path.
+    fn name(&self) -> String;
+
+    fn record(&self, vote: &Vote) -> Result<(), String>;
+}
+
+/// The thread that writes queued votes to the sinks, away from the runtime.
+pub struct SinkWriter {
+    queue: Arc<Mutex<Option<Sender<Vote>>>>,
+    thread: JoinHandle<()>,
+}
+
+impl SinkWriter {
+    /// Stops taking votes and waits until the queued ones are written.
+    pub fn close(self) {
+        self.queue.lock().unwrap().take();
+        if self.thread.join().is_err() {
+            log::error!("The vote sink writer panicked");
+        }
+    }
+}
+
+/// Records every vote emitted on `service` in each sink, in order. Doctor
+/// probes aren't votes and are left out. Sinks do blocking I/O, so votes are
+/// queued for a writer thread instead of being recorded in the emitter.
+pub fn attach(service: &VoteService, sinks: Vec<Box<dyn VoteSink>>) -> Result<Option<SinkWriter>, String> {
+    if sinks.is_empty() {
+        return Ok(None);
+    }
+
+    let (sender, votes) = mpsc::channel::<Vote>();
+    let thread = std::thread::Builder::new()
+        .name("voteme-sink".to_string())
+        .spawn(move || {
+            for vote in votes {
+                for sink in &sinks {
+                    if let Err(e) = sink.record(&vote) {
+                        log::error!("Failed to record vote from {} in {}: {e}", vote.username, sink.name());
+                    }
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to start the vote sink writer: {e}"))?;
+
+    let queue = Arc::new(Mutex::new(Some(sender)));
+    let listener_queue = queue.clone();
+    service.on_vote(move |vote| {
+        if doctor::is_doctor_vote(&vote) {
+            return;
+        }
+        match &*listener_queue.lock().unwrap() {
+            Some(sender) if sender.send(vote.clone()).is_ok() => {}
+            _ => log::warn!("Vote from {} arrived after the sinks closed", vote.username),
+        }
+    });
+
+    Ok(Some(SinkWriter { queue, thread }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use voteme_api::{Timestamp, VoteProtocol};
+
+    struct Recorded(Arc<Mutex<Vec<String>>>);
+
+    impl VoteSink for Recorded {
+        fn name(&self) -> String {
+            "memory".to_string()
+        }
+
+        fn record(&self, vote: &Vote) -> Result<(), String> {
+            self.0.lock().unwrap().push(vote.username.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn doctor_probes_are_not_recorded() {
+        let service = VoteService::new();
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+        let writer = attach(&service, vec![Box::new(Recorded(recorded.clone()))]).unwrap().unwrap();
+
+        let vote = |username: &str, probe: bool| Vote {
+            probe,
+            ..Vote::new("TestList", username, "", Timestamp::from_secs(1_700_000_000), VoteProtocol::V1)
+        };
+        service.emit(vote("VoteMeDoctor", true));
+        service.emit(vote("Alex", false));
+        writer.close();
+
+        assert_eq!(*recorded.lock().unwrap(), ["Alex"]);
+    }
+}
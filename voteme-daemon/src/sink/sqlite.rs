@@ -0,0 +1,113 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection};
+use voteme_api::Vote;
+use voteme_core::storage::VOTES_SCHEMA;
+
+use crate::sink::VoteSink;
+
+/// A `votes` table with the same layout as VoteReward's database, so either can
+/// read what the other wrote.
+pub struct SqliteSink {
+    path: PathBuf,
+    conn: Mutex<Connection>,
+}
+
+impl SqliteSink {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+        }
+
+        let conn = Connection::open(path).map_err(|e| format!("Failed to open sqlite db at {path:?}: {e}"))?;
+        conn.execute_batch(
+            "
+            PRAGMA journal_mode = WAL;
+            PRAGMA synchronous = NORMAL;
+            ",
+        )
+        .and_then(|_| conn.execute_batch(VOTES_SCHEMA))
+        .map_err(|e| format!("Failed to initialize sqlite schema: {e}"))?;
+        log::info!("Recording votes in {}", path.display());
+
+        Ok(SqliteSink {
+            path: path.to_path_buf(),
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+impl VoteSink for SqliteSink {
+    fn name(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn record(&self, vote: &Vote) -> Result<(), String> {
+        let vote_json = serde_json::to_string(vote).map_err(|e| e.to_string())?;
+
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "
+                INSERT INTO votes(service_name, username, address, vote_timestamp, received_at_ms, vote_json)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                ON CONFLICT(service_name, username, IFNULL(address, ''), IFNULL(vote_timestamp, '')) DO NOTHING
+                ",
+                params![
+                    vote.service_name,
+                    vote.username,
+                    vote.address,
+                    vote.timestamp.to_string(),
+                    vote.received_at.as_millis(),
+                    vote_json
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to insert vote: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use voteme_api::{Timestamp, VoteProtocol};
+
+    #[test]
+    fn votes_are_recorded_once() {
+        let dir = std::env::temp_dir().join(format!("voteme-daemon-sqlite-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("votes.db");
+
+        let vote = |username: &str| {
+            Vote::new("TestList", username, "198.51.100.9", Timestamp::from_secs(1_700_000_000), VoteProtocol::V2)
+        };
+        let sink = SqliteSink::open(&path).unwrap();
+        sink.record(&vote("Alex")).unwrap();
+        sink.record(&vote("Alex")).unwrap();
+        sink.record(&vote("Steve")).unwrap();
+        drop(sink);
+
+        let conn = Connection::open(&path).unwrap();
+        let mut statement = conn
+            .prepare("SELECT username, vote_timestamp, vote_json FROM votes ORDER BY id")
+            .unwrap();
+        let rows: Vec<(String, String, String)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].0, "Alex");
+        assert_eq!(rows[0].1, Timestamp::from_secs(1_700_000_000).to_string());
+        let recorded: Vote = serde_json::from_str(&rows[1].2).unwrap();
+        assert_eq!(recorded.username, "Steve");
+        assert_eq!(recorded.protocol, VoteProtocol::V2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -10,9 +10,6 @@ pub struct Config {
     #[serde(default)]
     pub database: DatabaseConfig,
 
-    #[serde(default)]
-    pub service: ServiceConfig,
-
     /// `%player%` placeholder to insert the voting player's username.
     #[serde(default)]
     pub rewards: Vec<String>,
@@ -35,38 +32,10 @@ impl Default for DatabaseConfig {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct ServiceConfig {
-    /// Service key registered by the VoteMe plugin.
-    #[serde(default = "default_service_key")]
-    pub key: String,
-
-    /// Retry delay while waiting for the service to appear.
-    #[serde(default = "default_retry_delay_ms")]
-    pub retry_delay_ms: u64,
-}
-
-impl Default for ServiceConfig {
-    fn default() -> Self {
-        Self {
-            key: default_service_key(),
-            retry_delay_ms: default_retry_delay_ms(),
-        }
-    }
-}
-
 fn default_db_path() -> String {
     "plugins/voteme-reward/voteme.sqlite".to_string()
 }
 
-fn default_service_key() -> String {
-    "voteme_service".to_string()
-}
-
-fn default_retry_delay_ms() -> u64 {
-    500
-}
-
 fn default_log_votes() -> bool {
     true
 }
@@ -77,10 +46,6 @@ impl Default for Config {
             database: DatabaseConfig {
                 path: default_db_path(),
             },
-            service: ServiceConfig {
-                key: default_service_key(),
-                retry_delay_ms: default_retry_delay_ms(),
-            },
             rewards: Vec::new(),
             log_votes: default_log_votes(),
         }
@@ -0,0 +1,59 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use pumpkin::command::CommandSender;
+use pumpkin::plugin::{Cancellable, EventHandler};
+use pumpkin::server::Server;
+use pumpkin_api_macros::with_runtime;
+use voteme_api::VoteEvent;
+
+use crate::storage::database::Database;
+
+/// Stores each vote and runs the reward commands for its player.
+/// Votes cancelled by a listener with a higher priority are skipped.
+pub struct RewardListener {
+    pub db: Arc<Database>,
+    pub rewards: Vec<String>,
+    pub log_votes: bool,
+}
+
+#[with_runtime(global)]
+impl EventHandler<VoteEvent> for RewardListener {
+    fn handle<'a>(
+        &'a self,
+        server: &'a Arc<Server>,
+        event: &'a VoteEvent,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+        Box::pin(async move {
+            if event.cancelled() {
+                return;
+            }
+
+            let vote = &event.vote;
+            if self.log_votes {
+                log::info!("Rewarding player: {}", vote.username);
+            }
+
+            if let Err(e) = self.db.insert_vote(vote) {
+                log::error!("Failed to persist vote to sqlite: {e}");
+            }
+
+            if self.rewards.is_empty() {
+                return;
+            }
+
+            let sender = CommandSender::Console;
+            let dispatcher = server.command_dispatcher.read().await;
+
+            for raw in self.rewards.iter() {
+                let cmd = raw.replace("%player%", &vote.username);
+                let cmd = cmd.trim();
+                if cmd.is_empty() {
+                    continue;
+                }
+
+                let cmd = cmd.strip_prefix('/').unwrap_or(cmd);
+                dispatcher.handle_command(&sender, server.as_ref(), cmd).await;
+            }
+        })
+    }
+}
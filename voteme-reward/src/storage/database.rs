@@ -2,12 +2,11 @@ use std::{
     fs,
     path::{Path, PathBuf},
     sync::Mutex,
-    time::{SystemTime, UNIX_EPOCH},
 };
 
 use rusqlite::{params, Connection};
-use serde_json::json;
 use voteme_api::Vote;
+use voteme_core::storage::VOTES_SCHEMA;
 
 pub struct Database {
     path: PathBuf,
@@ -50,43 +49,16 @@ impl Database {
             PRAGMA foreign_keys = ON;
             PRAGMA journal_mode = WAL;
             PRAGMA synchronous = NORMAL;
-
-            CREATE TABLE IF NOT EXISTS votes (
-                id            INTEGER PRIMARY KEY AUTOINCREMENT,
-                service_name   TEXT NOT NULL,
-                username       TEXT NOT NULL,
-                address        TEXT,
-                vote_timestamp TEXT,
-                received_at_ms INTEGER NOT NULL,
-                vote_json      TEXT NOT NULL
-            );
-
-            CREATE INDEX IF NOT EXISTS idx_votes_username ON votes(username);
-            CREATE INDEX IF NOT EXISTS idx_votes_service ON votes(service_name);
-            CREATE INDEX IF NOT EXISTS idx_votes_received ON votes(received_at_ms);
-
-            CREATE UNIQUE INDEX IF NOT EXISTS uniq_votes_natural
-            ON votes(service_name, username, IFNULL(address, ''), IFNULL(vote_timestamp, ''));
             ",
         )
+        .and_then(|_| conn.execute_batch(VOTES_SCHEMA))
         .map_err(|e| format!("Failed to initialize sqlite schema: {e}"))?;
 
         Ok(())
     }
 
     pub fn insert_vote(&self, vote: &Vote) -> Result<(), String> {
-        let received_at_ms = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis() as i64;
-
-        let vote_json = json!({
-            "service_name": vote.service_name,
-            "username": vote.username,
-            "address": vote.address,
-            "timestamp": vote.timestamp,
-        })
-        .to_string();
+        let vote_json = serde_json::to_string(vote).map_err(|e| format!("Failed to serialize vote: {e}"))?;
 
         let conn = self
             .conn
@@ -111,8 +83,8 @@ impl Database {
                 vote.service_name,
                 vote.username,
                 vote.address,
-                vote.timestamp,
-                received_at_ms,
+                vote.timestamp.to_string(),
+                vote.received_at.as_millis(),
                 vote_json
             ],
         )
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use pumpkin::command::args::{
+    Arg, ArgumentConsumer, ConsumeResult, GetClientSideArgParser, SuggestResult,
+};
+use pumpkin::command::tree::RawArgs;
+use pumpkin::command::CommandSender;
+use pumpkin::server::Server;
+use pumpkin_protocol::java::client::play::{
+    ArgumentType, CommandSuggestion, StringProto, SuggestionProviders,
+};
+use voteme_core::file::TokenStore;
+
+/// Single-word service name, tab-completed from the token store.
+pub struct ServiceArgumentConsumer {
+    tokens: Arc<TokenStore>,
+}
+
+impl ServiceArgumentConsumer {
+    pub fn new(tokens: Arc<TokenStore>) -> Self {
+        Self { tokens }
+    }
+}
+
+impl GetClientSideArgParser for ServiceArgumentConsumer {
+    fn get_client_side_parser(&self) -> ArgumentType<'_> {
+        ArgumentType::String(StringProto::SingleWord)
+    }
+
+    fn get_client_side_suggestion_type_override(&self) -> Option<SuggestionProviders> {
+        Some(SuggestionProviders::AskServer)
+    }
+}
+
+impl ArgumentConsumer for ServiceArgumentConsumer {
+    fn consume<'a>(
+        &'a self,
+        _sender: &'a CommandSender,
+        _server: &'a Server,
+        args: &mut RawArgs<'a>,
+    ) -> ConsumeResult<'a> {
+        let arg = args.pop().map(Arg::Simple);
+        Box::pin(async move { arg })
+    }
+
+    fn suggest<'a>(
+        &'a self,
+        _sender: &'a CommandSender,
+        _server: &'a Server,
+        input: &'a str,
+    ) -> SuggestResult<'a> {
+        Box::pin(async move {
+            let suggestions = self
+                .tokens
+                .services()
+                .into_iter()
+                .filter(|service| service.starts_with(input))
+                .map(|service| CommandSuggestion::new(service, None))
+                .collect();
+            Ok(Some(suggestions))
+        })
+    }
+}
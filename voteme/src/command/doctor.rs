@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use pumpkin::command::args::{Arg, ConsumedArgs};
+use pumpkin::command::{CommandExecutor, CommandResult, CommandSender};
+use pumpkin::server::Server;
+use pumpkin_util::text::TextComponent;
+use voteme_core::doctor::Doctor;
+use voteme_core::state::VoteMeState;
+
+use crate::command::{require_permission, ARG_SERVICE, PERMISSION_DOCTOR};
+
+/// `/voteme doctor [service]`: checks the setup end to end with two loopback votes.
+pub struct DoctorExecutor {
+    state: Arc<VoteMeState>,
+}
+
+impl DoctorExecutor {
+    pub fn new(state: Arc<VoteMeState>) -> Self {
+        Self { state }
+    }
+}
+
+impl CommandExecutor for DoctorExecutor {
+    fn execute<'a>(
+        &'a self,
+        sender: &'a CommandSender,
+        _server: &'a Server,
+        args: &'a ConsumedArgs<'a>,
+    ) -> CommandResult<'a> {
+        Box::pin(async move {
+            require_permission(sender, PERMISSION_DOCTOR).await?;
+
+            let service = match args.get(ARG_SERVICE) {
+                Some(Arg::Simple(service)) => Some(*service),
+                _ => None,
+            };
+
+            sender.send_message(TextComponent::text("Running VoteMe doctor...")).await;
+            let report = Doctor::run(&self.state, service).await;
+            for check in &report.checks {
+                sender.send_message(TextComponent::text(check.to_string())).await;
+            }
+
+            let summary = match report.failed_step() {
+                None => "All checks passed.".to_string(),
+                Some(step) => format!("Doctor failed at: {step}"),
+            };
+            log::info!("{}", summary);
+            sender.send_message(TextComponent::text(summary)).await;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use pumpkin::command::args::simple::SimpleArgConsumer;
+use pumpkin::command::dispatcher::CommandError;
+use pumpkin::command::tree::builder::{argument, literal};
+use pumpkin::command::tree::CommandTree;
+use pumpkin::command::CommandSender;
+use pumpkin::plugin::Context;
+use pumpkin_util::permission::{Permission, PermissionDefault, PermissionLvl};
+use voteme_core::state::VoteMeState;
+
+mod args;
+pub mod doctor;
+pub mod pubkey;
+pub mod reload;
+pub mod rotatekey;
+pub mod status;
+pub mod test_vote;
+pub mod token;
+
+use args::ServiceArgumentConsumer;
+
+const NAMES: [&str; 1] = ["voteme"];
+const DESCRIPTION: &str = "Manage the VoteMe vote receiver.";
+
+pub const PERMISSION: &str = "voteme:command.voteme";
+pub const PERMISSION_STATUS: &str = "voteme:command.voteme.status";
+pub const PERMISSION_RELOAD: &str = "voteme:command.voteme.reload";
+pub const PERMISSION_TEST: &str = "voteme:command.voteme.test";
+pub const PERMISSION_PUBKEY: &str = "voteme:command.voteme.pubkey";
+pub const PERMISSION_TOKEN: &str = "voteme:command.voteme.token";
+pub const PERMISSION_ROTATEKEY: &str = "voteme:command.voteme.rotatekey";
+pub const PERMISSION_DOCTOR: &str = "voteme:command.voteme.doctor";
+
+const ARG_PLAYER: &str = "player";
+const ARG_SERVICE: &str = "service";
+
+const PERMISSIONS: [(&str, &str); 8] = [
+    (PERMISSION, "Allows use of the /voteme command"),
+    (PERMISSION_STATUS, "Show listener state and vote counters"),
+    (PERMISSION_RELOAD, "Reload the VoteMe configuration"),
+    (PERMISSION_TEST, "Emit a synthetic test vote"),
+    (PERMISSION_PUBKEY, "Show the public key and vote site settings"),
+    (PERMISSION_TOKEN, "Show and manage v2 service tokens"),
+    (PERMISSION_ROTATEKEY, "Rotate the RSA keypair"),
+    (PERMISSION_DOCTOR, "Run a loopback self-test of the vote setup"),
+];
+
+/// Registers the permission nodes and the `/voteme` command tree.
+pub async fn register(server: &Context, state: Arc<VoteMeState>) -> Result<(), String> {
+    for (node, description) in PERMISSIONS {
+        server
+            .register_permission(Permission::new(
+                node,
+                description,
+                PermissionDefault::Op(PermissionLvl::Three),
+            ))
+            .await?;
+    }
+
+    server
+        .register_command(init_command_tree(state), PERMISSION)
+        .await;
+    Ok(())
+}
+
+pub fn init_command_tree(state: Arc<VoteMeState>) -> CommandTree {
+    let service = || ServiceArgumentConsumer::new(state.tokens.clone());
+
+    CommandTree::new(NAMES, DESCRIPTION)
+        .then(literal("status").execute(status::StatusExecutor::new(state.clone())))
+        .then(literal("reload").execute(reload::ReloadExecutor::new(state.clone())))
+        .then(
+            literal("test").then(
+                argument(ARG_PLAYER, SimpleArgConsumer)
+                    .execute(test_vote::TestVoteExecutor::new(state.clone()))
+                    .then(argument(ARG_SERVICE, service()).execute(test_vote::TestVoteExecutor::new(state.clone()))),
+            ),
+        )
+        .then(
+            literal("pubkey")
+                .execute(pubkey::PubkeyExecutor::new(state.clone()))
+                .then(argument(ARG_SERVICE, service()).execute(pubkey::PubkeyExecutor::new(state.clone()))),
+        )
+        .then(
+            literal("token").then(
+                argument(ARG_SERVICE, service())
+                    .execute(token::TokenExecutor::new(state.clone(), token::TokenAction::Show))
+                    .then(literal("add").execute(token::TokenExecutor::new(state.clone(), token::TokenAction::Add)))
+                    .then(literal("rotate").execute(token::TokenExecutor::new(state.clone(), token::TokenAction::Rotate)))
+                    .then(literal("revoke").execute(token::TokenExecutor::new(state.clone(), token::TokenAction::Revoke))),
+            ),
+        )
+        .then(literal("rotatekey").execute(rotatekey::RotateKeyExecutor::new(state.clone())))
+        .then(
+            literal("doctor")
+                .execute(doctor::DoctorExecutor::new(state.clone()))
+                .then(argument(ARG_SERVICE, service()).execute(doctor::DoctorExecutor::new(state))),
+        )
+}
+
+/// Sub-command permission check, on top of the root `/voteme` node.
+async fn require_permission(sender: &CommandSender, node: &str) -> Result<(), CommandError> {
+    if sender.has_permission(node).await {
+        Ok(())
+    } else {
+        Err(CommandError::PermissionDenied)
+    }
+}
@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use pumpkin::command::args::{Arg, ConsumedArgs};
+use pumpkin::command::{CommandExecutor, CommandResult, CommandSender};
+use pumpkin::server::Server;
+use pumpkin_util::text::TextComponent;
+use voteme_core::file::tokens::DEFAULT_SERVICE;
+use voteme_core::state::VoteMeState;
+
+use crate::command::{require_permission, ARG_SERVICE, PERMISSION_PUBKEY};
+
+/// `/voteme pubkey [service]`: prints what a vote site needs.
+pub struct PubkeyExecutor {
+    state: Arc<VoteMeState>,
+}
+
+impl PubkeyExecutor {
+    pub fn new(state: Arc<VoteMeState>) -> Self {
+        Self { state }
+    }
+}
+
+impl CommandExecutor for PubkeyExecutor {
+    fn execute<'a>(
+        &'a self,
+        sender: &'a CommandSender,
+        _server: &'a Server,
+        args: &'a ConsumedArgs<'a>,
+    ) -> CommandResult<'a> {
+        Box::pin(async move {
+            require_permission(sender, PERMISSION_PUBKEY).await?;
+
+            let service = match args.get(ARG_SERVICE) {
+                Some(Arg::Simple(service)) => *service,
+                _ => DEFAULT_SERVICE,
+            };
+
+            for line in self.state.vote_site_info(service) {
+                sender.send_message(TextComponent::text(line)).await;
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use pumpkin::command::args::ConsumedArgs;
+use pumpkin::command::dispatcher::CommandError;
+use pumpkin::command::{CommandExecutor, CommandResult, CommandSender};
+use pumpkin::server::Server;
+use pumpkin_util::text::TextComponent;
+use voteme_core::state::VoteMeState;
+
+use crate::command::{require_permission, PERMISSION_RELOAD};
+
+/// `/voteme reload`: re-reads the config and token files.
+pub struct ReloadExecutor {
+    state: Arc<VoteMeState>,
+}
+
+impl ReloadExecutor {
+    pub fn new(state: Arc<VoteMeState>) -> Self {
+        Self { state }
+    }
+}
+
+impl CommandExecutor for ReloadExecutor {
+    fn execute<'a>(
+        &'a self,
+        sender: &'a CommandSender,
+        _server: &'a Server,
+        _args: &'a ConsumedArgs<'a>,
+    ) -> CommandResult<'a> {
+        Box::pin(async move {
+            require_permission(sender, PERMISSION_RELOAD).await?;
+
+            self.state.reload().await.map_err(|e| {
+                CommandError::CommandFailed(Box::new(TextComponent::text(format!("Reload failed: {e}"))))
+            })?;
+
+            sender
+                .send_message(TextComponent::text("VoteMe configuration reloaded."))
+                .await;
+            Ok(())
+        })
+    }
+}
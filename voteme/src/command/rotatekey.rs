@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use pumpkin::command::args::ConsumedArgs;
+use pumpkin::command::dispatcher::CommandError;
+use pumpkin::command::{CommandExecutor, CommandResult, CommandSender};
+use pumpkin::server::Server;
+use pumpkin_util::text::TextComponent;
+use voteme_core::file::tokens::DEFAULT_SERVICE;
+use voteme_core::state::VoteMeState;
+
+use crate::command::{require_permission, PERMISSION_ROTATEKEY};
+
+/// `/voteme rotatekey`: new keypair; the old key keeps working for the grace period.
+pub struct RotateKeyExecutor {
+    state: Arc<VoteMeState>,
+}
+
+impl RotateKeyExecutor {
+    pub fn new(state: Arc<VoteMeState>) -> Self {
+        Self { state }
+    }
+}
+
+impl CommandExecutor for RotateKeyExecutor {
+    fn execute<'a>(
+        &'a self,
+        sender: &'a CommandSender,
+        _server: &'a Server,
+        _args: &'a ConsumedArgs<'a>,
+    ) -> CommandResult<'a> {
+        Box::pin(async move {
+            require_permission(sender, PERMISSION_ROTATEKEY).await?;
+
+            let config = self.state.config();
+            let keys = self.state.keys.clone();
+            let bits = config.rsa_bits as usize;
+
+            // Key generation takes a while; keep it off the async workers.
+            let rotated = tokio::task::spawn_blocking(move || keys.rotate(bits))
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r.map_err(|e| e.to_string()))
+                .map_err(|e| {
+                    CommandError::CommandFailed(Box::new(TextComponent::text(format!("Rotation failed: {e}"))))
+                })?;
+
+            sender
+                .send_message(TextComponent::text(format!(
+                    "Rotated to key {}. The previous key is accepted for {}h; update your vote sites:",
+                    rotated.label(),
+                    config.key_grace_hours
+                )))
+                .await;
+            for line in self.state.vote_site_info(DEFAULT_SERVICE) {
+                sender.send_message(TextComponent::text(line)).await;
+            }
+            Ok(())
+        })
+    }
+}
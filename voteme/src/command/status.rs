@@ -0,0 +1,125 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use pumpkin::command::args::ConsumedArgs;
+use pumpkin::command::{CommandExecutor, CommandResult, CommandSender};
+use pumpkin::server::Server;
+use pumpkin_util::text::TextComponent;
+use voteme_core::state::{VoteMeState, VoteStats};
+
+use crate::command::{require_permission, PERMISSION_STATUS};
+
+/// `/voteme status`: listener state, uptime and vote counters.
+pub struct StatusExecutor {
+    state: Arc<VoteMeState>,
+}
+
+impl StatusExecutor {
+    pub fn new(state: Arc<VoteMeState>) -> Self {
+        Self { state }
+    }
+}
+
+fn format_uptime(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60);
+    format!("{days}d {hours}h {minutes}m {}s", secs % 60)
+}
+
+impl CommandExecutor for StatusExecutor {
+    fn execute<'a>(
+        &'a self,
+        sender: &'a CommandSender,
+        _server: &'a Server,
+        _args: &'a ConsumedArgs<'a>,
+    ) -> CommandResult<'a> {
+        Box::pin(async move {
+            require_permission(sender, PERMISSION_STATUS).await?;
+
+            let state = &self.state;
+            let stats = &state.stats;
+            let listeners = state.listeners.lock().unwrap().clone();
+            let config = state.config();
+            let banned = state.limiter.banned(Instant::now());
+
+            let mut lines: Vec<String> = listeners
+                .iter()
+                .map(|(address, status)| format!("Listener {}: {}", address, status))
+                .collect();
+            lines.extend([
+                format!("Uptime: {}", format_uptime(state.started_at.elapsed().as_secs())),
+                format!(
+                    "Votes: {} accepted, {} rejected, {} duplicates, {} errors ({} connections)",
+                    VoteStats::get(&stats.accepted),
+                    VoteStats::get(&stats.rejected),
+                    VoteStats::get(&stats.duplicates),
+                    VoteStats::get(&stats.errors),
+                    VoteStats::get(&stats.connections),
+                ),
+                format!(
+                    "Connections: {}/{} active, {} over the cap, {} timed out",
+                    VoteStats::get(&stats.active),
+                    config.max_connections,
+                    VoteStats::get(&stats.over_capacity),
+                    VoteStats::get(&stats.timeouts),
+                ),
+                format!(
+                    "Rate limit: {}/min (burst {}), {} refused, {} IPs banned",
+                    config.rate_limit_per_minute,
+                    config.rate_limit_burst,
+                    VoteStats::get(&stats.rate_limited),
+                    banned.len(),
+                ),
+            ]);
+            for (ip, left) in banned.iter().take(5) {
+                lines.push(format!("  banned {} for {}m", ip, left.as_secs().div_ceil(60)));
+            }
+            lines.push(format!(
+                "IP filter: {} allow, {} deny, {} service lists, {} refused",
+                config.ip_filter.global.allow.len(),
+                config.ip_filter.global.deny.len(),
+                config.ip_filter.services.len(),
+                VoteStats::get(&stats.filtered),
+            ));
+            if config.proxy_protocol {
+                lines.push(format!(
+                    "PROXY protocol: {} trusted ranges, {} proxied connections",
+                    config.trusted_proxies.len(),
+                    VoteStats::get(&stats.proxied),
+                ));
+            }
+            if config.http.enabled {
+                lines.push(format!(
+                    "HTTP: POST {} ({} requests)",
+                    config.http.path,
+                    VoteStats::get(&stats.http_requests),
+                ));
+            }
+            if !config.pollers.is_empty() {
+                lines.push(format!(
+                    "Pollers: {} providers, {} votes polled, {} failed polls",
+                    config.pollers.len(),
+                    VoteStats::get(&stats.polled),
+                    VoteStats::get(&stats.poll_errors),
+                ));
+            }
+            let forwarders = state.forwarders.lock().unwrap().clone();
+            for target in &config.forward {
+                let status = forwarders.get(&target.name).cloned().unwrap_or_default();
+                lines.push(format!("Forward {} ({}): {}", target.name, target.address, status));
+            }
+            if !config.forward.is_empty() {
+                lines.push(format!(
+                    "Forwarded: {} delivered, {} failed attempts",
+                    VoteStats::get(&stats.forwarded),
+                    VoteStats::get(&stats.forward_failures),
+                ));
+            }
+            lines.push(format!("Key: {}", state.keys.current().label()));
+
+            for line in lines {
+                sender.send_message(TextComponent::text(line)).await;
+            }
+            Ok(())
+        })
+    }
+}
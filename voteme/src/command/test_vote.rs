@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use pumpkin::command::args::{Arg, ConsumedArgs};
+use pumpkin::command::dispatcher::CommandError;
+use pumpkin::command::{CommandExecutor, CommandResult, CommandSender};
+use pumpkin::server::Server;
+use pumpkin_util::text::TextComponent;
+use voteme_api::{Timestamp, Vote, VoteProtocol};
+use voteme_core::state::VoteMeState;
+
+use crate::command::{require_permission, ARG_PLAYER, ARG_SERVICE, PERMISSION_TEST};
+
+const TEST_SERVICE: &str = "VoteMe";
+
+/// `/voteme test <player> [service]`: emits a synthetic vote to all listeners.
+pub struct TestVoteExecutor {
+    state: Arc<VoteMeState>,
+}
+
+impl TestVoteExecutor {
+    pub fn new(state: Arc<VoteMeState>) -> Self {
+        Self { state }
+    }
+}
+
+impl CommandExecutor for TestVoteExecutor {
+    fn execute<'a>(
+        &'a self,
+        sender: &'a CommandSender,
+        _server: &'a Server,
+        args: &'a ConsumedArgs<'a>,
+    ) -> CommandResult<'a> {
+        Box::pin(async move {
+            require_permission(sender, PERMISSION_TEST).await?;
+
+            let Some(Arg::Simple(player)) = args.get(ARG_PLAYER) else {
+                return Err(CommandError::InvalidConsumption(Some(ARG_PLAYER.into())));
+            };
+            let service = match args.get(ARG_SERVICE) {
+                Some(Arg::Simple(service)) => *service,
+                _ => TEST_SERVICE,
+            };
+
+            self.state
+                .vote_service
+                .emit(Vote::new(service, *player, "127.0.0.1", Timestamp::now(), VoteProtocol::Test));
+            log::info!("Emitted test vote for {} from {}", player, service);
+
+            sender
+                .send_message(TextComponent::text(format!("Sent test vote for {player} from {service}.")))
+                .await;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use pumpkin::command::args::{Arg, ConsumedArgs};
+use pumpkin::command::dispatcher::CommandError;
+use pumpkin::command::{CommandExecutor, CommandResult, CommandSender};
+use pumpkin::server::Server;
+use pumpkin_util::text::TextComponent;
+use voteme_core::state::VoteMeState;
+
+use crate::command::{require_permission, ARG_SERVICE, PERMISSION_TOKEN};
+
+#[derive(Clone, Copy)]
+pub enum TokenAction {
+    Show,
+    Add,
+    Rotate,
+    Revoke,
+}
+
+/// `/voteme token <service> [add|rotate|revoke]`
+pub struct TokenExecutor {
+    state: Arc<VoteMeState>,
+    action: TokenAction,
+}
+
+impl TokenExecutor {
+    pub fn new(state: Arc<VoteMeState>, action: TokenAction) -> Self {
+        Self { state, action }
+    }
+}
+
+impl CommandExecutor for TokenExecutor {
+    fn execute<'a>(
+        &'a self,
+        sender: &'a CommandSender,
+        _server: &'a Server,
+        args: &'a ConsumedArgs<'a>,
+    ) -> CommandResult<'a> {
+        Box::pin(async move {
+            require_permission(sender, PERMISSION_TOKEN).await?;
+
+            let Some(Arg::Simple(service)) = args.get(ARG_SERVICE) else {
+                return Err(CommandError::InvalidConsumption(Some(ARG_SERVICE.into())));
+            };
+            let tokens = &self.state.tokens;
+            let failed = |e: String| CommandError::CommandFailed(Box::new(TextComponent::text(e)));
+
+            let message = match self.action {
+                TokenAction::Show => match tokens.get(service) {
+                    Some(token) => format!("Token ({service}): {token}"),
+                    None => format!("No token for {service}. Use /voteme token {service} add"),
+                },
+                TokenAction::Add => {
+                    let token = tokens.add(service, None).map_err(failed)?;
+                    format!("Added token ({service}): {token}")
+                }
+                TokenAction::Rotate => {
+                    let token = tokens.rotate(service).map_err(failed)?;
+                    format!("New token ({service}): {token}")
+                }
+                TokenAction::Revoke => {
+                    if tokens.revoke(service).map_err(failed)? {
+                        format!("Revoked token for {service}.")
+                    } else {
+                        format!("No token for {service}.")
+                    }
+                }
+            };
+
+            sender.send_message(TextComponent::text(message)).await;
+            Ok(())
+        })
+    }
+}
@@ -1,4 +0,0 @@
-pub mod RSA;
-pub mod RSAKeyGen;
-pub mod RSAIO;
-pub mod AES;
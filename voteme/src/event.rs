@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use pumpkin::plugin::{Cancellable, PluginManager};
+use tokio::sync::mpsc;
+use voteme_api::{Vote, VoteEvent, VoteService};
+use voteme_core::doctor;
+
+/// Fires every accepted vote as a [`VoteEvent`], so other plugins can listen
+/// with Pumpkin's event system instead of looking up the `voteme_service`.
+pub struct VoteEvents;
+
+impl VoteEvents {
+    /// Listens on `service` for emitted votes; the receiver feeds [`VoteEvents::run`].
+    /// Doctor probes stay inside VoteMe, so they can't trigger rewards.
+    pub fn subscribe(service: &VoteService) -> mpsc::UnboundedReceiver<Vote> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        service.on_vote(move |vote| {
+            if doctor::is_doctor_vote(&vote) {
+                return;
+            }
+            let _ = tx.send(vote);
+        });
+        rx
+    }
+
+    /// Fires the votes one at a time, in the order they were accepted.
+    pub async fn run(plugin_manager: Arc<PluginManager>, mut votes: mpsc::UnboundedReceiver<Vote>) {
+        while let Some(vote) = votes.recv().await {
+            let event = plugin_manager.fire(VoteEvent::new(vote)).await;
+            if event.cancelled() {
+                log::info!(
+                    "Vote from {} for service {} was cancelled by a listener",
+                    event.vote.username, event.vote.service_name
+                );
+            }
+        }
+    }
+}
@@ -1,3 +0,0 @@
-pub mod config;
-
-pub use config::{Config, ConfigManager};
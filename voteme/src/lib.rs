@@ -1,119 +1,82 @@
-use std::{path::Path, sync::{Arc, Mutex}};
+use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
 
-use pumpkin::plugin::{Context};
+use pumpkin::plugin::Context;
+use pumpkin::server::Server;
 use pumpkin_api_macros::{plugin_impl, plugin_method};
-use tokio::{io::AsyncWriteExt, net::TcpListener};
 
-use file::config::ConfigManager;
-use net::vote_handler::VoteHandler;
-use crypto::{RSAIO, RSAKeyGen};
-use file::Config;
-use voteme_api::{Vote, VoteService};
+use voteme_api::VoteService;
+use voteme_core::net::listener::ListenerHandle;
+use voteme_core::poll::PlayerSource;
+use voteme_core::state::VoteMeState;
 
-mod crypto;
-mod file;
-mod net;
-mod parser;
-pub mod vote;
+mod command;
+mod event;
 
 #[plugin_method]
 async fn on_load(&mut self, server: Arc<Context>) -> Result<(), String> {
     server.init_log();
     log::info!("VoteMe plugin loading...");
 
-    let mut config = Config::default();
-    let mut config_manager = ConfigManager::new_default();
-    config_manager.init_config(&mut config).await?;
-
-    // Avoid moving `config` into the background thread (it would prevent later use).
-    let host = config.host.clone();
-    let port = config.port;
-    let debug = config.debug;
-
-    let privkey = if Path::new("plugins/VoteMe/rsa/private.key").exists() {
-        RSAIO::load_private("private.key")
-    } else {
-        let (privkey, pubkey) = RSAKeyGen::generate(config.rsa_bits as usize);
-        RSAIO::save_private(&privkey, "private.key");
-        RSAIO::save_public(&pubkey, "public.key");
-        privkey
-    };
-
     let vote_service = Arc::new(VoteService::new());
+    let votes = event::VoteEvents::subscribe(&vote_service);
+    let state = Arc::new(VoteMeState::load(vote_service.clone()).await?);
     server
-        .register_service("voteme_service", vote_service.clone())
+        .register_service("voteme_service", vote_service)
         .await;
 
-    let server_ctx = server.clone();
-    std::thread::spawn(move || {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-
-        rt.block_on(async move {
-            let bind_addr = format!("{}:{}", host, port);
-
-            let listener = match TcpListener::bind(&bind_addr).await {
-                Ok(l) => l,
-                Err(e) => {
-                    log::error!("Bind failed: {}", e);
-                    return;
-                }
-            };
-
-            loop {
-                match listener.accept().await {
-                    Ok((mut socket, addr)) => {
-                        let key = privkey.clone();
-                        let vote_service = vote_service.clone();
-
-                        tokio::spawn(async move {
-                            if debug {
-                                log::debug!("Accepted vote connection from {}", addr);
-                            }
-
-                            let _ = socket.write_all(b"VOTIFIER 1.9\n").await;
-                            let result = match VoteHandler::handle_v1(&mut socket, &key).await {
-                                Ok(v) => Ok(v),
-                                Err(_) => VoteHandler::handle_v2(&mut socket).await,
-                            };
-
-                            match result {
-                                Ok(vote) => {
-                                    log::info!(
-                                        "Received vote from {} for service {}",
-                                        vote.username, vote.service_name
-                                    );
-
-                                    vote_service.emit(Vote {
-                                        service_name: vote.service_name,
-                                        username: vote.username,
-                                        address: vote.address,
-                                        timestamp: vote.timestamp,
-                                    });
-                                }
-                                Err(e) => {
-                                    log::warn!("Vote error {}: {}", addr, e);
-                                }
-                            }
-                        });
-                    }
-                    Err(e) => {
-                        log::error!("Accept error: {}", e);
-                    }
-                }
-            }
-        });
-    });
+    command::register(&server, state.clone()).await?;
+
+    let players = Arc::new(OnlinePlayers(server.server.clone()));
+    let listener = ListenerHandle::start(state.clone(), players)?;
+    listener.spawn(event::VoteEvents::run(server.plugin_manager.clone(), votes));
+    self.listener = Some(listener);
+    self.state = Some(state);
     log::info!("VoteMe plugin loaded successfully.");
 
     Ok(())
 }
 
+#[plugin_method]
+async fn on_unload(&mut self, _server: Arc<Context>) -> Result<(), String> {
+    log::info!("VoteMe plugin unloading...");
+
+    if let (Some(listener), Some(state)) = (self.listener.take(), self.state.take()) {
+        let drain = Duration::from_secs(state.config.read().unwrap().shutdown_timeout_secs);
+        listener.shutdown(drain).await;
+    }
+
+    log::info!("VoteMe plugin unloaded.");
+    Ok(())
+}
+
+/// Online players, for pollers that ask about each one.
+struct OnlinePlayers(Arc<Server>);
+
+impl PlayerSource for OnlinePlayers {
+    fn online_players(&self) -> Pin<Box<dyn Future<Output = Vec<String>> + Send + '_>> {
+        Box::pin(async move {
+            self.0
+                .get_all_players()
+                .await
+                .iter()
+                .map(|player| player.gameprofile.name.clone())
+                .collect()
+        })
+    }
+}
+
 #[plugin_impl]
-pub struct VoteMe;
+pub struct VoteMe {
+    listener: Option<ListenerHandle>,
+    state: Option<Arc<VoteMeState>>,
+}
 
 impl VoteMe {
     pub fn new() -> Self {
-        VoteMe
+        VoteMe {
+            listener: None,
+            state: None,
+        }
     }
 }
 
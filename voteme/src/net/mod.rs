@@ -1 +0,0 @@
-pub mod vote_handler;